@@ -44,7 +44,7 @@ fn main() -> ! {
         cs,
     );
 
-    let mut display = ab1024_ega::Display::new(spi, rst, dc, busy, delay);
+    let mut display = ab1024_ega::inkplate6color::display(spi, rst, dc, busy, delay);
 
     Circle::with_center(Point::new(150, 224), 200)
         .into_styled(PrimitiveStyle::with_fill(ab1024_ega::color::Color::RED))
@@ -59,7 +59,7 @@ fn main() -> ! {
         .draw(&mut display)
         .unwrap();
 
-    display.init().unwrap();
+    let mut display = display.init().unwrap();
     display.display().unwrap();
 
     Rtc::new(peripherals.LPWR).sleep_deep(&[], &mut delay)
@@ -71,13 +71,13 @@ fn main() -> ! {
     );
 
     let bmp: Bmp<Rgb888> = Bmp::from_slice(include_bytes!("starry-night.bmp")).unwrap();
-    let mut display = ab1024_ega::Display::new(spi, rst, dc, busy, delay);
+    let mut display = ab1024_ega::inkplate6color::display(spi, rst, dc, busy, delay);
     let mut ed: DitherTarget<'_, _, _, { ab1024_ega::WIDTH }> =
         DitherTarget::new(&mut display, &conversion);
 
     bmp.draw(&mut ed).unwrap();
 
-    display.init().unwrap();
+    let mut display = display.init().unwrap();
     display.display().unwrap();
 
     Rtc::new(peripherals.LPWR).sleep_deep(&[], &mut delay)
@@ -0,0 +1,255 @@
+//! Pre-processing of [`Rgb888`] input before it is reduced to the panel's inks.
+//!
+//! The inks are far less saturated than sRGB, so photos mapped straight to [`crate::color::Color`]
+//! tend to look washed out.  [`Adjustments`] describes corrections to apply first, and
+//! [`Adjusted`] applies them in front of any [`Quantizer`].  All arithmetic is fixed point.
+
+use crate::{color::Color, quantize::Quantizer};
+use embedded_graphics_core::pixelcolor::{Rgb888, RgbColor};
+use fixed::types::I16F16;
+
+/// Fixed point number used for adjustment parameters.
+pub type Fixed = I16F16;
+
+const HALF: Fixed = Fixed::from_bits(1 << 15);
+const MAX: Fixed = Fixed::from_bits(255 << 16);
+
+/// `2^(2^-i)` for `i` in `1..=16`, used by [`exp2`].
+const ROOTS_OF_TWO: [Fixed; 16] = [
+    Fixed::from_bits(92682),
+    Fixed::from_bits(77936),
+    Fixed::from_bits(71468),
+    Fixed::from_bits(68438),
+    Fixed::from_bits(66971),
+    Fixed::from_bits(66250),
+    Fixed::from_bits(65892),
+    Fixed::from_bits(65714),
+    Fixed::from_bits(65625),
+    Fixed::from_bits(65580),
+    Fixed::from_bits(65558),
+    Fixed::from_bits(65547),
+    Fixed::from_bits(65542),
+    Fixed::from_bits(65539),
+    Fixed::from_bits(65537),
+    Fixed::from_bits(65537),
+];
+
+/// Corrections applied to each pixel, in this order:
+///
+/// 1. `white_point` is scaled to white
+/// 2. `gamma`
+/// 3. `brightness`
+/// 4. `contrast`
+/// 5. `tone_curve`
+/// 6. `saturation`
+///
+/// [`Adjustments::default`] changes nothing.
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+pub struct Adjustments<'a> {
+    /// Values above 1 brighten mid-tones, values below 1 darken them.  Must be positive.
+    pub gamma: Fixed,
+    /// Added to each channel, where 1 is full scale.
+    pub brightness: Fixed,
+    /// Scales each channel about mid-gray.
+    pub contrast: Fixed,
+    /// Scales each channel's distance from the pixel's luma.  0 gives grayscale.
+    pub saturation: Fixed,
+    /// The input color that should come out white, e.g. the color of paper in a photo.
+    pub white_point: Rgb888,
+    /// Points `(input, output)` of a piecewise linear curve applied to each channel, sorted by
+    /// input.  Empty for no curve.
+    pub tone_curve: &'a [(u8, u8)],
+}
+
+impl Default for Adjustments<'_> {
+    fn default() -> Self {
+        Self {
+            gamma: Fixed::ONE,
+            brightness: Fixed::ZERO,
+            contrast: Fixed::ONE,
+            saturation: Fixed::ONE,
+            white_point: Rgb888::WHITE,
+            tone_curve: &[],
+        }
+    }
+}
+
+impl Adjustments<'_> {
+    /// Precomputes the adjustments into lookup tables.
+    pub fn build(&self) -> Preprocessor {
+        Preprocessor {
+            tables: [
+                self.table(self.white_point.r()),
+                self.table(self.white_point.g()),
+                self.table(self.white_point.b()),
+            ],
+            saturation: self.saturation,
+        }
+    }
+
+    /// Per-channel adjustments for every input value of a channel whose white point is `white`.
+    fn table(&self, white: u8) -> [u8; 256] {
+        let white = Fixed::from_num(white.max(1));
+        let exponent = if self.gamma > Fixed::ZERO {
+            Fixed::ONE.saturating_div(self.gamma)
+        } else {
+            Fixed::ONE
+        };
+        let mut table = [0; 256];
+
+        for (input, output) in (0..=255u8).zip(table.iter_mut()) {
+            let mut value = (Fixed::from_num(input) / white).min(Fixed::ONE);
+            value = pow(value, exponent);
+            value = value.saturating_add(self.brightness);
+            value = value
+                .saturating_sub(HALF)
+                .saturating_mul(self.contrast)
+                .saturating_add(HALF);
+            let value = (value.clamp(Fixed::ZERO, Fixed::ONE) * MAX)
+                .round()
+                .to_num::<u8>();
+            *output = tone(self.tone_curve, value);
+        }
+
+        table
+    }
+}
+
+/// [`Adjustments`] ready to apply to pixels.
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+pub struct Preprocessor {
+    tables: [[u8; 256]; 3],
+    saturation: Fixed,
+}
+
+impl Preprocessor {
+    pub fn apply(&self, color: Rgb888) -> Rgb888 {
+        let channels = [
+            self.tables[0][usize::from(color.r())],
+            self.tables[1][usize::from(color.g())],
+            self.tables[2][usize::from(color.b())],
+        ];
+        if self.saturation == Fixed::ONE {
+            return Rgb888::new(channels[0], channels[1], channels[2]);
+        }
+
+        let luma = (u32::from(channels[0]) * 77
+            + u32::from(channels[1]) * 150
+            + u32::from(channels[2]) * 29)
+            >> 8;
+        let luma = Fixed::from_num(luma);
+        let [r, g, b] = channels.map(|channel| {
+            luma.saturating_add((Fixed::from_num(channel) - luma).saturating_mul(self.saturation))
+                .clamp(Fixed::ZERO, MAX)
+                .round()
+                .to_num::<u8>()
+        });
+        Rgb888::new(r, g, b)
+    }
+}
+
+/// Applies a [`Preprocessor`] to pixels before passing them on to a [`Quantizer`].
+pub struct Adjusted<Q> {
+    preprocessor: Preprocessor,
+    quantizer: Q,
+}
+
+impl<Q> Adjusted<Q>
+where
+    Q: Quantizer,
+{
+    pub fn new(adjustments: &Adjustments<'_>, quantizer: Q) -> Self {
+        Self {
+            preprocessor: adjustments.build(),
+            quantizer,
+        }
+    }
+
+    pub fn into_inner(self) -> Q {
+        self.quantizer
+    }
+}
+
+impl<Q> Quantizer for Adjusted<Q>
+where
+    Q: Quantizer,
+{
+    fn quantize(&mut self, x: usize, y: usize, color: Rgb888) -> Color {
+        self.quantizer
+            .quantize(x, y, self.preprocessor.apply(color))
+    }
+}
+
+/// Interpolates `value` along `curve`.
+fn tone(curve: &[(u8, u8)], value: u8) -> u8 {
+    let Some(&(first_in, first_out)) = curve.first() else {
+        return value;
+    };
+    if value <= first_in {
+        return first_out;
+    }
+
+    for pair in curve.windows(2) {
+        let [(x0, y0), (x1, y1)] = [pair[0], pair[1]];
+        if value <= x1 {
+            let (x0, y0, x1, y1) = (i32::from(x0), i32::from(y0), i32::from(x1), i32::from(y1));
+            let interpolated = y0 + (y1 - y0) * (i32::from(value) - x0) / (x1 - x0).max(1);
+            return u8::try_from(interpolated.clamp(0, 255)).unwrap_or(u8::MAX);
+        }
+    }
+
+    curve[curve.len() - 1].1
+}
+
+/// `base` raised to `exponent`, for `base` between 0 and 1 and positive `exponent`.
+fn pow(base: Fixed, exponent: Fixed) -> Fixed {
+    if base <= Fixed::ZERO {
+        Fixed::ZERO
+    } else if exponent == Fixed::ONE {
+        base
+    } else {
+        exp2(log2(base).saturating_mul(exponent))
+    }
+}
+
+/// Base 2 logarithm of `value` between 0 and 1, one bit at a time.
+fn log2(value: Fixed) -> Fixed {
+    // value = mantissa * 2^-shift, with mantissa in [1, 2)
+    let mut mantissa = value;
+    let mut result = Fixed::ZERO;
+    while mantissa < Fixed::ONE {
+        mantissa <<= 1;
+        result -= Fixed::ONE;
+    }
+
+    let mut bit = HALF;
+    for _ in 0..16 {
+        mantissa *= mantissa;
+        if mantissa >= 2 {
+            mantissa >>= 1;
+            result += bit;
+        }
+        bit >>= 1;
+    }
+    result
+}
+
+/// 2 raised to non-positive `value`, from the bits of its fraction.
+fn exp2(value: Fixed) -> Fixed {
+    let whole = value.floor();
+    let fraction = value - whole;
+
+    let mut result = Fixed::ONE;
+    for (i, root) in ROOTS_OF_TWO.iter().enumerate() {
+        if fraction.to_bits() & (1 << (15 - i)) != 0 {
+            result *= *root;
+        }
+    }
+
+    let shift = whole.saturating_neg().to_num::<u32>();
+    if shift >= 32 {
+        Fixed::ZERO
+    } else {
+        result >> shift
+    }
+}
@@ -0,0 +1,255 @@
+//! Colored glyphs, such as icons and emoji, stored in the panel's packed layout.
+//!
+//! An atlas is made with the `ab1024-ega-atlas` tool and can be included with
+//! [`include_bytes!`].  It is laid out as follows, with numbers little-endian:
+//!
+//! | Bytes     | Content                                                   |
+//! |-----------|-----------------------------------------------------------|
+//! | 4         | [`MAGIC`]                                                 |
+//! | 1         | glyph width in pixels                                     |
+//! | 1         | glyph height in pixels                                    |
+//! | 2         | number of glyphs, `n`                                     |
+//! | `4 * n`   | the character of each glyph, as a `u32`                   |
+//! | remainder | glyphs side by side, as one packed image `n` glyphs wide  |
+//!
+//! Pixels that are [`TRANSPARENT`] are left out when a glyph is drawn.  With the `text`
+//! feature, [`GlyphAtlas::draw_text`] mixes glyphs into text drawn with a mono font.
+
+use crate::{
+    color::Color,
+    interface::Interface,
+    packed::{Canvas, PackedImage},
+    state::PowerState,
+    Display,
+};
+use core::fmt;
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{InputPin, OutputPin},
+};
+
+/// First bytes of an atlas.
+pub const MAGIC: [u8; 4] = *b"GLA4";
+/// Nibble of transparent pixels, one past the last [`Color`].
+pub const TRANSPARENT: u8 = 0x7;
+
+const HEADER_LEN: usize = 8;
+
+/// Why [`GlyphAtlas::new`] could not read an atlas.
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The data does not start with an atlas header.
+    InvalidHeader,
+    /// The data ended before all glyphs.
+    Truncated,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::InvalidHeader => "not a glyph atlas",
+            Self::Truncated => "glyph atlas truncated",
+        })
+    }
+}
+
+impl core::error::Error for Error {}
+
+/// Glyphs borrowed from atlas data.
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+pub struct GlyphAtlas<'a> {
+    characters: &'a [u8],
+    strip: PackedImage<'a>,
+    glyph_width: usize,
+    glyph_height: usize,
+}
+
+impl<'a> GlyphAtlas<'a> {
+    /// # Errors
+    ///
+    /// [`Error::InvalidHeader`] if `data` is not an atlas, or [`Error::Truncated`] if it is cut
+    /// short.
+    pub fn new(data: &'a [u8]) -> Result<Self, Error> {
+        let header = data.get(..HEADER_LEN).ok_or(Error::InvalidHeader)?;
+        if header[..4] != MAGIC {
+            return Err(Error::InvalidHeader);
+        }
+        let glyph_width = usize::from(header[4]);
+        let glyph_height = usize::from(header[5]);
+        let count = usize::from(u16::from_le_bytes([header[6], header[7]]));
+
+        let (characters, pixels) = data[HEADER_LEN..]
+            .split_at_checked(count * 4)
+            .ok_or(Error::Truncated)?;
+        let strip_width = glyph_width * count;
+        let pixels = pixels
+            .get(..strip_width.div_ceil(2) * glyph_height)
+            .ok_or(Error::Truncated)?;
+
+        Ok(Self {
+            characters,
+            strip: PackedImage::new(pixels, strip_width),
+            glyph_width,
+            glyph_height,
+        })
+    }
+
+    pub fn glyph_width(&self) -> usize {
+        self.glyph_width
+    }
+
+    pub fn glyph_height(&self) -> usize {
+        self.glyph_height
+    }
+
+    /// The glyph for `character`, if the atlas has one.
+    pub fn glyph(&self, character: char) -> Option<Glyph<'a>> {
+        let index = self.characters.chunks_exact(4).position(|bytes| {
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) == u32::from(character)
+        })?;
+        Some(Glyph {
+            strip: self.strip,
+            x: index * self.glyph_width,
+            width: self.glyph_width,
+        })
+    }
+}
+
+/// A glyph from a [`GlyphAtlas`].
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+pub struct Glyph<'a> {
+    strip: PackedImage<'a>,
+    x: usize,
+    width: usize,
+}
+
+impl Glyph<'_> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.strip.height()
+    }
+
+    /// The pixel at (`x`, `y`), or `None` if it is transparent or lies outside the glyph.
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Color> {
+        if x >= self.width {
+            return None;
+        }
+        self.strip.pixel(self.x + x, y)
+    }
+}
+
+impl<B> Canvas<B>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /// Draws `glyph` with its top left corner at (`x`, `y`), leaving out transparent pixels and
+    /// anything outside the canvas.
+    pub fn draw_glyph(&mut self, glyph: &Glyph<'_>, x: usize, y: usize) {
+        self.draw_columns_keyed(&glyph.strip, glyph.x, glyph.width, x, y, TRANSPARENT);
+    }
+}
+
+impl<D, DI, RST, BUSY, STATE> Display<D, DI, RST, BUSY, STATE>
+where
+    D: DelayNs,
+    DI: Interface,
+    RST: OutputPin,
+    BUSY: InputPin,
+    STATE: PowerState,
+{
+    /// Draws `glyph` into the [`Display`] buffer with its top left corner at (`x`, `y`), leaving
+    /// out transparent pixels and anything outside the panel.
+    pub fn draw_glyph(&mut self, glyph: &Glyph<'_>, x: usize, y: usize) {
+        self.canvas_mut().draw_glyph(glyph, x, y);
+    }
+}
+
+#[cfg(feature = "text")]
+mod text {
+    use super::{Canvas, Display, GlyphAtlas};
+    use crate::{color::Color, interface::Interface, state::PowerState};
+    use embedded_graphics::{
+        mono_font::MonoTextStyle,
+        prelude::Point,
+        text::{Baseline, Text},
+        Drawable,
+    };
+    use embedded_hal::{
+        delay::DelayNs,
+        digital::{InputPin, OutputPin},
+    };
+
+    impl GlyphAtlas<'_> {
+        /// Draws `text` onto `canvas` with its top left corner at `position`.  Characters with a
+        /// glyph in the atlas are drawn from it, top-aligned with the text, and the rest with
+        /// `style`.  Returns the position following the last character.
+        pub fn draw_text<B>(
+            &self,
+            canvas: &mut Canvas<B>,
+            text: &str,
+            mut position: Point,
+            style: MonoTextStyle<'_, Color>,
+        ) -> Point
+        where
+            B: AsRef<[u8]> + AsMut<[u8]>,
+        {
+            let spacing = i32::try_from(style.font.character_spacing).unwrap_or(0);
+            let advance = i32::try_from(self.glyph_width).unwrap_or(i32::MAX) + spacing;
+
+            // runs of characters without a glyph are drawn as one Text
+            let mut run_start = 0;
+            for (index, character) in text.char_indices() {
+                let Some(glyph) = self.glyph(character) else {
+                    continue;
+                };
+                position = draw_run(canvas, &text[run_start..index], position, style);
+                if let (Ok(x), Ok(y)) = (usize::try_from(position.x), usize::try_from(position.y)) {
+                    canvas.draw_glyph(&glyph, x, y);
+                }
+                position.x += advance;
+                run_start = index + character.len_utf8();
+            }
+            draw_run(canvas, &text[run_start..], position, style)
+        }
+    }
+
+    fn draw_run<B>(
+        canvas: &mut Canvas<B>,
+        run: &str,
+        position: Point,
+        style: MonoTextStyle<'_, Color>,
+    ) -> Point
+    where
+        B: AsRef<[u8]> + AsMut<[u8]>,
+    {
+        if run.is_empty() {
+            return position;
+        }
+        let Ok(next) = Text::with_baseline(run, position, style, Baseline::Top).draw(canvas);
+        next
+    }
+
+    impl<D, DI, RST, BUSY, STATE> Display<D, DI, RST, BUSY, STATE>
+    where
+        D: DelayNs,
+        DI: Interface,
+        RST: OutputPin,
+        BUSY: InputPin,
+        STATE: PowerState,
+    {
+        /// Like [`GlyphAtlas::draw_text`] but onto the [`Display`] buffer.
+        pub fn draw_text(
+            &mut self,
+            atlas: &GlyphAtlas<'_>,
+            text: &str,
+            position: Point,
+            style: MonoTextStyle<'_, Color>,
+        ) -> Point {
+            atlas.draw_text(&mut self.canvas_mut(), text, position, style)
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Builds a glyph atlas for [`ab1024_ega::atlas::GlyphAtlas`] from PNG, JPEG or BMP images, one
+//! per glyph.
+//!
+//! Pixels less than half opaque become transparent and the rest take the nearest ink.
+
+use ab1024_ega::{
+    atlas::{MAGIC, TRANSPARENT},
+    color::Color,
+};
+use clap::Parser;
+use embedded_graphics_core::pixelcolor::Rgb888;
+use image::{imageops, imageops::FilterType, RgbaImage};
+use std::{error::Error, fs, path::PathBuf};
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Builds a glyph atlas for AB1024-EGA/AC057TC1 panels from one image per glyph"
+)]
+struct Args {
+    /// File to write
+    output: PathBuf,
+    /// Glyphs as CHARACTER=IMAGE, where CHARACTER is a single character or U+XXXX
+    #[arg(required = true, value_parser = parse_glyph)]
+    glyphs: Vec<(char, PathBuf)>,
+    /// Size of each glyph in pixels, as WIDTHxHEIGHT, taken from the first image by default
+    #[arg(long, value_parser = parse_size)]
+    size: Option<(u32, u32)>,
+}
+
+fn parse_glyph(glyph: &str) -> Result<(char, PathBuf), String> {
+    let invalid = || format!("expected CHARACTER=IMAGE, got {glyph:?}");
+    let (character, path) = glyph.split_once('=').ok_or_else(invalid)?;
+    let character = match character.strip_prefix("U+") {
+        Some(hex) => u32::from_str_radix(hex, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(invalid)?,
+        None => {
+            let mut chars = character.chars();
+            match (chars.next(), chars.next()) {
+                (Some(character), None) => character,
+                _ => return Err(invalid()),
+            }
+        }
+    };
+    Ok((character, PathBuf::from(path)))
+}
+
+fn parse_size(size: &str) -> Result<(u32, u32), String> {
+    let invalid = || format!("expected WIDTHxHEIGHT of at most 255x255, got {size:?}");
+    let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+    let width: u8 = width.parse().map_err(|_| invalid())?;
+    let height: u8 = height.parse().map_err(|_| invalid())?;
+    if width == 0 || height == 0 {
+        return Err(invalid());
+    }
+    Ok((width.into(), height.into()))
+}
+
+/// The nibble for a pixel.
+fn nibble(pixel: image::Rgba<u8>) -> u8 {
+    let [r, g, b, a] = pixel.0;
+    if a < 0x80 {
+        TRANSPARENT
+    } else {
+        Color::from(Rgb888::new(r, g, b)).into()
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    let mut images = Vec::new();
+    for (_, path) in &args.glyphs {
+        images.push(image::open(path)?.into_rgba8());
+    }
+    let (width, height) = args
+        .size
+        .unwrap_or_else(|| (images[0].width(), images[0].height()));
+    let images: Vec<RgbaImage> = images
+        .into_iter()
+        .map(|image| {
+            if image.dimensions() == (width, height) {
+                image
+            } else {
+                imageops::resize(&image, width, height, FilterType::Lanczos3)
+            }
+        })
+        .collect();
+
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(u8::try_from(width).map_err(|_| "glyphs must be at most 255 pixels wide")?);
+    bytes.push(u8::try_from(height).map_err(|_| "glyphs must be at most 255 pixels high")?);
+    bytes.extend(u16::try_from(args.glyphs.len())?.to_le_bytes());
+    for (character, _) in &args.glyphs {
+        bytes.extend(u32::from(*character).to_le_bytes());
+    }
+
+    // glyphs side by side, two pixels per byte with the left one in the high nibble
+    let strip_width = width as usize * images.len();
+    for y in 0..height {
+        let mut row = vec![0; strip_width.div_ceil(2)];
+        for (i, image) in images.iter().enumerate() {
+            for x in 0..width {
+                let strip_x = i * width as usize + x as usize;
+                let shift = if strip_x.is_multiple_of(2) { 4 } else { 0 };
+                row[strip_x / 2] |= nibble(*image.get_pixel(x, y)) << shift;
+            }
+        }
+        bytes.extend(row);
+    }
+
+    fs::write(&args.output, bytes)?;
+    Ok(())
+}
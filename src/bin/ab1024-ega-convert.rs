@@ -0,0 +1,409 @@
+//! Converts PNG, JPEG or BMP images into the packed format sent to the panel, so that they can be
+//! drawn with [`ab1024_ega::packed::PackedImage`] or copied straight into the `Display` buffer.
+//!
+//! A full-size image (the default `--size 600x448`) has exactly the layout of the frame that
+//! `Display` sends after `DATA_START_TRANS_REGISTER`.
+
+use ab1024_ega::{
+    adjust::{Adjustments, Fixed},
+    color::Color,
+    packed::Canvas,
+};
+use clap::{error::ErrorKind, CommandFactory, Parser, ValueEnum};
+use embedded_graphics_core::pixelcolor::{Rgb888, RgbColor};
+use image::{imageops, imageops::FilterType, Rgb, RgbImage};
+use std::{
+    error::Error,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Converts images to the packed format used by AB1024-EGA/AC057TC1 panels"
+)]
+struct Args {
+    /// PNG, JPEG or BMP image to convert
+    input: PathBuf,
+    /// File to write
+    output: PathBuf,
+    /// Layout of the output file
+    #[arg(long, value_enum, default_value_t = Format::Bin)]
+    format: Format,
+    /// Size of the output in pixels, as WIDTHxHEIGHT
+    #[arg(long, default_value = "600x448", value_parser = parse_size)]
+    size: (u32, u32),
+    /// How the input is scaled to the output size
+    #[arg(long, value_enum, default_value_t = Resize::Fit)]
+    resize: Resize,
+    /// Clockwise rotation applied before scaling
+    #[arg(long, value_enum, default_value_t = Rotate::R0)]
+    rotate: Rotate,
+    #[arg(long, value_enum, default_value_t = Dither::FloydSteinberg)]
+    dither: Dither,
+    /// Colors that pixels are matched against
+    #[arg(long, value_enum, default_value_t = Palette::Measured)]
+    palette: Palette,
+    /// Values above 1 brighten mid-tones
+    #[arg(long, default_value = "1", value_parser = parse_fixed)]
+    gamma: Fixed,
+    /// Added to each channel, where 1 is full scale
+    #[arg(long, default_value = "0", value_parser = parse_fixed, allow_negative_numbers = true)]
+    brightness: Fixed,
+    #[arg(long, default_value = "1", value_parser = parse_fixed)]
+    contrast: Fixed,
+    /// Values above 1 make up for the inks being less saturated than a screen
+    #[arg(long, default_value = "1", value_parser = parse_fixed)]
+    saturation: Fixed,
+    /// Also write a PNG showing roughly how the panel will look
+    #[arg(long)]
+    preview: Option<PathBuf>,
+    /// Name of the array for `rust` and `c` output, taken from the output file by default
+    #[arg(long)]
+    name: Option<String>,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum Format {
+    /// Raw packed bytes
+    Bin,
+    /// Raw packed bytes next to the output, plus a Rust file that includes them with
+    /// `include_bytes!`
+    Rust,
+    /// C header with the packed bytes as an array
+    C,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum Resize {
+    /// Scale to fit inside the output, padding with white
+    Fit,
+    /// Scale to cover the output, cropping the overflow
+    Fill,
+    /// Scale to the output size, ignoring aspect ratio
+    Stretch,
+    /// Keep the input size, cropping or padding with white around the center
+    None,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum Rotate {
+    #[value(name = "0")]
+    R0,
+    #[value(name = "90")]
+    R90,
+    #[value(name = "180")]
+    R180,
+    #[value(name = "270")]
+    R270,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum Dither {
+    /// Nearest color only
+    None,
+    FloydSteinberg,
+    Atkinson,
+    /// 4x4 Bayer matrix
+    Ordered,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum Palette {
+    /// How the inks actually look on the panel
+    Measured,
+    /// Pure primaries, for more saturated but less accurate results
+    Saturated,
+    /// Black and white only
+    Mono,
+}
+
+impl Palette {
+    fn colors(self) -> Vec<(Color, [f32; 3])> {
+        let saturated = |color| match color {
+            Color::BLACK => [0, 0, 0],
+            Color::WHITE => [255, 255, 255],
+            Color::GREEN => [0, 255, 0],
+            Color::BLUE => [0, 0, 255],
+            Color::RED => [255, 0, 0],
+            Color::YELLOW => [255, 255, 0],
+            Color::ORANGE => [255, 128, 0],
+        };
+        let measured = |color| {
+            let rgb = Rgb888::from(color);
+            [rgb.r(), rgb.g(), rgb.b()]
+        };
+
+        (0..7)
+            .filter_map(|value| Color::try_from(value).ok())
+            .filter(|color| {
+                !matches!(self, Self::Mono) || matches!(color, Color::BLACK | Color::WHITE)
+            })
+            .map(|color| {
+                let rgb = match self {
+                    Self::Saturated => saturated(color),
+                    Self::Measured | Self::Mono => measured(color),
+                };
+                (color, rgb.map(f32::from))
+            })
+            .collect()
+    }
+}
+
+fn parse_size(size: &str) -> Result<(u32, u32), String> {
+    let invalid = || format!("expected WIDTHxHEIGHT, got {size:?}");
+    let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+    let width = width.parse().map_err(|_| invalid())?;
+    let height = height.parse().map_err(|_| invalid())?;
+    if width == 0 || height == 0 {
+        return Err(invalid());
+    }
+    Ok((width, height))
+}
+
+fn parse_fixed(value: &str) -> Result<Fixed, String> {
+    value
+        .parse::<f32>()
+        .ok()
+        .and_then(Fixed::checked_from_num)
+        .ok_or_else(|| {
+            format!(
+                "expected a number between {} and {}",
+                Fixed::MIN,
+                Fixed::MAX
+            )
+        })
+}
+
+/// Rotates and scales `image` onto a white background of `width` by `height`.
+fn prepare(image: RgbImage, args: &Args) -> RgbImage {
+    let (width, height) = args.size;
+    let image = match args.rotate {
+        Rotate::R0 => image,
+        Rotate::R90 => imageops::rotate90(&image),
+        Rotate::R180 => imageops::rotate180(&image),
+        Rotate::R270 => imageops::rotate270(&image),
+    };
+
+    let scaled = match args.resize {
+        Resize::Fit => {
+            let scale = f64::min(
+                f64::from(width) / f64::from(image.width()),
+                f64::from(height) / f64::from(image.height()),
+            );
+            imageops::resize(
+                &image,
+                scaled(image.width(), scale),
+                scaled(image.height(), scale),
+                FilterType::Lanczos3,
+            )
+        }
+        Resize::Fill => {
+            let scale = f64::max(
+                f64::from(width) / f64::from(image.width()),
+                f64::from(height) / f64::from(image.height()),
+            );
+            imageops::resize(
+                &image,
+                scaled(image.width(), scale),
+                scaled(image.height(), scale),
+                FilterType::Lanczos3,
+            )
+        }
+        Resize::Stretch => imageops::resize(&image, width, height, FilterType::Lanczos3),
+        Resize::None => image,
+    };
+
+    let mut output = RgbImage::from_pixel(width, height, Rgb([255, 255, 255]));
+    let x = (i64::from(width) - i64::from(scaled.width())) / 2;
+    let y = (i64::from(height) - i64::from(scaled.height())) / 2;
+    imageops::replace(&mut output, &scaled, x, y);
+    output
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn scaled(length: u32, scale: f64) -> u32 {
+    (f64::from(length) * scale).round().max(1.0) as u32
+}
+
+fn nearest(palette: &[(Color, [f32; 3])], rgb: [f32; 3]) -> (Color, [f32; 3]) {
+    let distance = |candidate: &[f32; 3]| {
+        candidate
+            .iter()
+            .zip(rgb)
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f32>()
+    };
+    palette
+        .iter()
+        .copied()
+        .min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)))
+        .unwrap()
+}
+
+/// Reduces `image` to `palette`, returning one [`Color`] per pixel in row-major order.
+fn quantize(image: &RgbImage, palette: &[(Color, [f32; 3])], dither: Dither) -> Vec<Color> {
+    const BAYER: [[f32; 4]; 4] = [
+        [0.0, 8.0, 2.0, 10.0],
+        [12.0, 4.0, 14.0, 6.0],
+        [3.0, 11.0, 1.0, 9.0],
+        [15.0, 7.0, 13.0, 5.0],
+    ];
+    // (dx, dy, weight) of the error passed on to neighbouring pixels
+    let kernel: &[(i64, i64, f32)] = match dither {
+        Dither::FloydSteinberg => &[
+            (1, 0, 7.0 / 16.0),
+            (-1, 1, 3.0 / 16.0),
+            (0, 1, 5.0 / 16.0),
+            (1, 1, 1.0 / 16.0),
+        ],
+        Dither::Atkinson => &[
+            (1, 0, 1.0 / 8.0),
+            (2, 0, 1.0 / 8.0),
+            (-1, 1, 1.0 / 8.0),
+            (0, 1, 1.0 / 8.0),
+            (1, 1, 1.0 / 8.0),
+            (0, 2, 1.0 / 8.0),
+        ],
+        Dither::None | Dither::Ordered => &[],
+    };
+
+    let width = i64::from(image.width());
+    let height = i64::from(image.height());
+    let mut pixels: Vec<[f32; 3]> = image.pixels().map(|p| p.0.map(f32::from)).collect();
+    let mut colors = Vec::with_capacity(pixels.len());
+
+    for y in 0..height {
+        for x in 0..width {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let index = (y * width + x) as usize;
+            let mut wanted = pixels[index];
+            if let Dither::Ordered = dither {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let threshold = BAYER[(y % 4) as usize][(x % 4) as usize] / 16.0 - 0.5;
+                wanted = wanted.map(|channel| channel + threshold * 64.0);
+            }
+            let (color, actual) = nearest(palette, wanted);
+            colors.push(color);
+
+            for &(dx, dy, weight) in kernel {
+                let (nx, ny) = (x + dx, y + dy);
+                if (0..width).contains(&nx) && ny < height {
+                    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                    let neighbour = &mut pixels[(ny * width + nx) as usize];
+                    for channel in 0..3 {
+                        neighbour[channel] += (wanted[channel] - actual[channel]) * weight;
+                    }
+                }
+            }
+        }
+    }
+
+    colors
+}
+
+fn pack(colors: &[Color], width: usize) -> Vec<u8> {
+    let rows = colors.len() / width;
+    let mut canvas = Canvas::new(vec![0; width.div_ceil(2) * rows], width);
+    for (i, color) in colors.iter().enumerate() {
+        canvas.set_pixel(i % width, i / width, *color);
+    }
+    canvas.into_inner()
+}
+
+fn preview(colors: &[Color], width: u32, height: u32) -> RgbImage {
+    RgbImage::from_fn(width, height, |x, y| {
+        let rgb = Rgb888::from(colors[(y * width + x) as usize]);
+        Rgb([rgb.r(), rgb.g(), rgb.b()])
+    })
+}
+
+/// Turns the stem of `path` into an identifier, e.g. `splash-screen.h` into `splash_screen`.
+fn identifier(path: &Path) -> String {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let mut name: String = stem
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        name.insert(0, '_');
+    }
+    name
+}
+
+fn c_header(name: &str, bytes: &[u8], width: u32, height: u32) -> String {
+    let upper = name.to_ascii_uppercase();
+    let mut header = format!(
+        "#pragma once\n\n#include <stdint.h>\n\n#define {upper}_WIDTH {width}\n#define {upper}_HEIGHT {height}\n\nstatic const uint8_t {name}[{}] = {{\n",
+        bytes.len()
+    );
+    for line in bytes.chunks(12) {
+        header.push_str("   ");
+        for byte in line {
+            write!(header, " 0x{byte:02x},").unwrap();
+        }
+        header.push('\n');
+    }
+    header.push_str("};\n");
+    header
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let (width, height) = args.size;
+    if matches!(args.format, Format::Rust) && args.output.with_extension("bin") == args.output {
+        Args::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "`--format rust` writes the packed bytes to OUTPUT with a .bin extension, so OUTPUT \
+                 cannot end in .bin",
+            )
+            .exit();
+    }
+
+    let mut image = prepare(image::open(&args.input)?.into_rgb8(), &args);
+    let preprocessor = Adjustments {
+        gamma: args.gamma,
+        brightness: args.brightness,
+        contrast: args.contrast,
+        saturation: args.saturation,
+        ..Adjustments::default()
+    }
+    .build();
+    for pixel in image.pixels_mut() {
+        let rgb = preprocessor.apply(Rgb888::new(pixel[0], pixel[1], pixel[2]));
+        *pixel = Rgb([rgb.r(), rgb.g(), rgb.b()]);
+    }
+    let colors = quantize(&image, &args.palette.colors(), args.dither);
+    let bytes = pack(&colors, width.try_into()?);
+    let name = args
+        .name
+        .clone()
+        .unwrap_or_else(|| identifier(&args.output));
+
+    match args.format {
+        Format::Bin => fs::write(&args.output, &bytes)?,
+        Format::Rust => {
+            let bin = args.output.with_extension("bin");
+            fs::write(&bin, &bytes)?;
+            let file_name = bin.file_name().unwrap_or_default().to_string_lossy();
+            fs::write(
+                &args.output,
+                format!(
+                    "pub const {upper}_WIDTH: usize = {width};\npub const {upper}_HEIGHT: usize = {height};\npub static {upper}: &[u8; {}] = include_bytes!({file_name:?});\n",
+                    bytes.len(),
+                    upper = name.to_ascii_uppercase(),
+                ),
+            )?;
+        }
+        Format::C => fs::write(&args.output, c_header(&name, &bytes, width, height))?,
+    }
+
+    if let Some(path) = &args.preview {
+        preview(&colors, width, height).save(path)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,187 @@
+//! Run-length compression of frames, so that more of them fit in flash.
+//!
+//! Two pixels share each byte of a frame, so areas of one ink or of a repeating 2-pixel
+//! pattern are runs of the same byte.  Data is a series of tokens, each a header byte
+//! followed by:
+//!
+//! - header `0x00` to `0x7f`: `header + 1` literal bytes
+//! - header `0x80` to `0xff`: one byte repeated `header - 0x80 + 2` times
+//!
+//! [`Decompressor`] streams frames back out into the [`Display`] buffer with
+//! [`Display::draw_compressed`], which reports corrupt data.  It can also feed the `fill`
+//! callback of [`Display::display_with`], needing no buffer beyond its chunk, but only for
+//! data known to be good: `fill` cannot fail, so errors are lost and the frame is refreshed
+//! anyway.  Retries are unsupported there, as a [`Decompressor`] cannot start over when
+//! [`crate::config::PanelConfig::verify_transfer`] sends a frame again.
+
+use crate::{interface::Interface, state::PowerState, Display};
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{InputPin, OutputPin},
+};
+
+const MAX_LITERAL: usize = 0x80;
+const MIN_REPEAT: usize = 3;
+const MAX_REPEAT: usize = 0x81;
+
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The output buffer was too small for the compressed data.
+    OutputFull,
+    /// The data ended part way through a token.
+    Truncated,
+    /// The data decompressed to fewer bytes than a frame.
+    TooShort,
+    /// The data decompressed to more bytes than a frame.
+    TooLong,
+}
+
+/// Size of the largest possible compressed form of `len` bytes, for sizing output buffers.
+pub const fn max_compressed_len(len: usize) -> usize {
+    len + len.div_ceil(MAX_LITERAL)
+}
+
+/// Compresses `input` into `output`, returning the compressed length.
+///
+/// # Errors
+///
+/// [`Error::OutputFull`] if `output` is shorter than needed.  It never is if at least
+/// [`max_compressed_len`] long.
+pub fn compress(input: &[u8], output: &mut [u8]) -> Result<usize, Error> {
+    let mut written = 0;
+    let mut push = |bytes: &[u8]| -> Result<(), Error> {
+        let end = written + bytes.len();
+        output
+            .get_mut(written..end)
+            .ok_or(Error::OutputFull)?
+            .copy_from_slice(bytes);
+        written = end;
+        Ok(())
+    };
+
+    let mut literal_start = 0;
+    let mut position = 0;
+    while position < input.len() {
+        let byte = input[position];
+        let run = input[position..]
+            .iter()
+            .take(MAX_REPEAT)
+            .take_while(|&&other| other == byte)
+            .count();
+
+        if run >= MIN_REPEAT {
+            for literal in input[literal_start..position].chunks(MAX_LITERAL) {
+                push(&[literal_header(literal.len())])?;
+                push(literal)?;
+            }
+            #[allow(clippy::cast_possible_truncation)]
+            push(&[0x80 + (run - 2) as u8, byte])?;
+            position += run;
+            literal_start = position;
+        } else {
+            position += run;
+        }
+    }
+    for literal in input[literal_start..].chunks(MAX_LITERAL) {
+        push(&[literal_header(literal.len())])?;
+        push(literal)?;
+    }
+
+    Ok(written)
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn literal_header(len: usize) -> u8 {
+    (len - 1) as u8
+}
+
+/// Streams data made by [`compress`] back out, a slice at a time.
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+pub struct Decompressor<'a> {
+    data: &'a [u8],
+    /// Bytes left in the current token.
+    literal: usize,
+    repeat: usize,
+    repeated: u8,
+}
+
+impl<'a> Decompressor<'a> {
+    pub const fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            literal: 0,
+            repeat: 0,
+            repeated: 0,
+        }
+    }
+
+    /// Whether all data has been decompressed.
+    pub fn is_finished(&self) -> bool {
+        self.data.is_empty() && self.repeat == 0 && self.literal == 0
+    }
+
+    /// Decompresses the next bytes into `out`, returning how many were written.  This is less
+    /// than `out.len()` only once the data is finished.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Truncated`] if the data ends part way through a token.
+    pub fn fill(&mut self, out: &mut [u8]) -> Result<usize, Error> {
+        let mut written = 0;
+        while written < out.len() {
+            if self.repeat > 0 {
+                let len = self.repeat.min(out.len() - written);
+                out[written..written + len].fill(self.repeated);
+                self.repeat -= len;
+                written += len;
+            } else if self.literal > 0 {
+                let len = self.literal.min(out.len() - written);
+                let bytes = self.data.get(..len).ok_or(Error::Truncated)?;
+                out[written..written + len].copy_from_slice(bytes);
+                self.data = &self.data[len..];
+                self.literal -= len;
+                written += len;
+            } else if let Some((&header, rest)) = self.data.split_first() {
+                if header < 0x80 {
+                    self.literal = usize::from(header) + 1;
+                    self.data = rest;
+                } else {
+                    let (&byte, rest) = rest.split_first().ok_or(Error::Truncated)?;
+                    self.repeat = usize::from(header - 0x80) + 2;
+                    self.repeated = byte;
+                    self.data = rest;
+                }
+            } else {
+                break;
+            }
+        }
+        Ok(written)
+    }
+}
+
+impl<D, DI, RST, BUSY, STATE> Display<D, DI, RST, BUSY, STATE>
+where
+    D: DelayNs,
+    DI: Interface,
+    RST: OutputPin,
+    BUSY: InputPin,
+    STATE: PowerState,
+{
+    /// Decompresses a whole frame from `decompressor` into the [`Display`] buffer.
+    ///
+    /// # Errors
+    ///
+    /// Errors from `decompressor` are passed through.  [`Error::TooShort`] is returned if it
+    /// finishes before the buffer is full, and [`Error::TooLong`] if it has data left once the
+    /// buffer is full.
+    pub fn draw_compressed(&mut self, decompressor: &mut Decompressor<'_>) -> Result<(), Error> {
+        if decompressor.fill(&mut self.buffer)? < self.buffer.len() {
+            return Err(Error::TooShort);
+        }
+        if !decompressor.is_finished() {
+            return Err(Error::TooLong);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+//! Controller settings that go beyond the fixed init sequence.
+
+/// Supply voltage below which the controller's low-voltage detector trips.
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LowPowerThreshold {
+    V2_2,
+    V2_3,
+    V2_4,
+    /// The controller's default.
+    V2_5,
+}
+
+impl LowPowerThreshold {
+    pub(crate) fn bits(self) -> u8 {
+        match self {
+            Self::V2_2 => 0b00,
+            Self::V2_3 => 0b01,
+            Self::V2_4 => 0b10,
+            Self::V2_5 => 0b11,
+        }
+    }
+}
+
+/// Rate at which the controller drives frames while refreshing, set through its PLL.  Higher
+/// rates refresh faster, at the cost of color fidelity.
+///
+/// Only the rates of the controller's `M = 7` PLL column are offered, i.e. 200 Hz divided by
+/// 1 to 7.  The slower rates the other `M` values give are not selectable.
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FrameRate {
+    Hz29,
+    Hz33,
+    Hz40,
+    /// The controller's default.
+    Hz50,
+    Hz67,
+    Hz100,
+    Hz200,
+}
+
+impl FrameRate {
+    /// Value of the PLL control register, as `M` in bits 5:3 and `N` in bits 2:0 giving
+    /// 200 Hz / `N`.
+    pub(crate) fn bits(self) -> u8 {
+        let n = match self {
+            Self::Hz200 => 1,
+            Self::Hz100 => 2,
+            Self::Hz67 => 3,
+            Self::Hz50 => 4,
+            Self::Hz40 => 5,
+            Self::Hz33 => 6,
+            Self::Hz29 => 7,
+        };
+        0b111_000 | n
+    }
+}
+
+/// VCOM voltage, as printed on the panel's flex cable.  The controller sets it in 50 mV steps
+/// from -0.1 V down to -4.0 V.
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Vcom(u8);
+
+impl Vcom {
+    pub const MAX_MV: i16 = -100;
+    pub const MIN_MV: i16 = -4_000;
+
+    /// The step nearest to `millivolts`, or `None` if it is outside the controller's range.
+    pub fn from_millivolts(millivolts: i16) -> Option<Self> {
+        if !(Self::MIN_MV..=Self::MAX_MV).contains(&millivolts) {
+            return None;
+        }
+        let steps = (Self::MAX_MV - millivolts + 25) / 50;
+        u8::try_from(steps).ok().map(Self)
+    }
+
+    pub fn millivolts(self) -> i16 {
+        Self::MAX_MV - i16::from(self.0) * 50
+    }
+
+    pub(crate) fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub(crate) fn from_bits(bits: u8) -> Self {
+        Self(bits & 0x7f)
+    }
+}
+
+/// Settings sent to the controller whenever it is woken.  [`PanelConfig::default`] leaves
+/// the controller as configured by the Soldered Inkplate library.
+///
+/// `refuse_low_power` and `verify_transfer` read from the controller, so they need an
+/// interface that can read (see [`crate::interface::Interface::read_data`]).
+#[derive(Default, Debug, Eq, Hash, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PanelConfig {
+    /// `None` keeps the controller's default.
+    pub frame_rate: Option<FrameRate>,
+    /// `None` keeps the controller's default.
+    pub vcom: Option<Vcom>,
+    /// Threshold of the low-voltage detector.  `None` keeps the controller's default.
+    pub low_power_threshold: Option<LowPowerThreshold>,
+    /// Checks the low-voltage detector before each refresh and fails with
+    /// [`crate::error::Error::LowPower`] instead of refreshing.  A refresh that browns out
+    /// part way through can leave the panel half drawn.
+    pub refuse_low_power: bool,
+    /// Asks the controller whether it received a whole frame before refreshing, failing with
+    /// [`crate::error::Error::TransferIncomplete`] if it did not.
+    pub verify_transfer: bool,
+    /// Times an incomplete frame is sent again before giving up, when `verify_transfer` is
+    /// set.
+    pub transfer_retries: u8,
+}
@@ -0,0 +1,99 @@
+//! Drawing other [`PixelColor`] types onto targets of [`Color`].
+//!
+//! [`Converted`] wraps a [`crate::Display`] or [`crate::packed::Canvas`] so that code written
+//! for e.g. `DrawTarget<Color = BinaryColor>` or `DrawTarget<Color = Rgb565>` works unchanged.
+//!
+//! [`BinaryColor`]: embedded_graphics_core::pixelcolor::BinaryColor
+//! [`Rgb565`]: embedded_graphics_core::pixelcolor::Rgb565
+
+use crate::color::Color;
+use core::marker::PhantomData;
+use embedded_graphics_core::{
+    draw_target::DrawTarget, pixelcolor::PixelColor, prelude::Dimensions, primitives::Rectangle,
+    Pixel,
+};
+
+/// Draws `C` onto a target of [`Color`], mapping each color through `F`.
+pub struct Converted<'a, T, C, F = fn(C) -> Color> {
+    target: &'a mut T,
+    map: F,
+    color: PhantomData<C>,
+}
+
+impl<'a, T, C> Converted<'a, T, C>
+where
+    T: DrawTarget<Color = Color>,
+    C: PixelColor + Into<Color>,
+{
+    /// Maps colors through their [`Into<Color>`] conversion.
+    pub fn new(target: &'a mut T) -> Self {
+        Self::with_map(target, Into::into)
+    }
+}
+
+impl<'a, T, C, F> Converted<'a, T, C, F>
+where
+    T: DrawTarget<Color = Color>,
+    C: PixelColor,
+    F: FnMut(C) -> Color,
+{
+    /// Maps colors through `map`, e.g. to draw [`BinaryColor::On`] in red.
+    ///
+    /// [`BinaryColor::On`]: embedded_graphics_core::pixelcolor::BinaryColor::On
+    pub fn with_map(target: &'a mut T, map: F) -> Self {
+        Self {
+            target,
+            map,
+            color: PhantomData,
+        }
+    }
+}
+
+impl<T, C, F> Dimensions for Converted<'_, T, C, F>
+where
+    T: DrawTarget<Color = Color>,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<T, C, F> DrawTarget for Converted<'_, T, C, F>
+where
+    T: DrawTarget<Color = Color>,
+    C: PixelColor,
+    F: FnMut(C) -> Color,
+{
+    type Color = C;
+    type Error = T::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let map = &mut self.map;
+        self.target.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(point, color)| Pixel(point, map(color))),
+        )
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.target
+            .fill_contiguous(area, colors.into_iter().map(&mut self.map))
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let color = (self.map)(color);
+        self.target.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let color = (self.map)(color);
+        self.target.clear(color)
+    }
+}
@@ -0,0 +1,90 @@
+//! Streaming image decoders that write straight into the [`Display`] buffer.
+//!
+//! Images are decoded one row at a time so that the decoded image is never held in memory.
+//! Rows are reduced to [`crate::color::Color`] by a [`Quantizer`].
+
+#[cfg(feature = "png")]
+pub mod png;
+#[cfg(feature = "qoi")]
+pub mod qoi;
+
+use crate::{interface::Interface, quantize::Quantizer, state::PowerState, Display, HEIGHT, WIDTH};
+use embedded_graphics_core::{
+    pixelcolor::{Rgb888, RgbColor},
+    prelude::Size,
+};
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{InputPin, OutputPin},
+};
+
+/// An image that can be decoded row by row.
+pub trait Decoder {
+    type Error;
+
+    fn size(&self) -> Size;
+
+    /// Decodes the next row.  Only the first `row.len()` pixels are written; the rest of the row
+    /// is decoded and discarded.
+    ///
+    /// # Errors
+    ///
+    /// Malformed or unsupported image data.
+    fn read_row(&mut self, row: &mut [Rgb888]) -> Result<(), Self::Error>;
+}
+
+impl<D, DI, RST, BUSY, STATE> Display<D, DI, RST, BUSY, STATE>
+where
+    D: DelayNs,
+    DI: Interface,
+    RST: OutputPin,
+    BUSY: InputPin,
+    STATE: PowerState,
+{
+    /// Decodes `decoder` into the [`Display`] buffer with its top left corner at (`x`, `y`).
+    /// Pixels falling outside the panel are discarded, and decoding stops at the bottom edge.
+    ///
+    /// # Errors
+    ///
+    /// Errors from `decoder` are passed through.
+    pub fn draw_decoded<DE, Q>(
+        &mut self,
+        decoder: &mut DE,
+        quantizer: &mut Q,
+        x: usize,
+        y: usize,
+    ) -> Result<(), DE::Error>
+    where
+        DE: Decoder,
+        Q: Quantizer,
+    {
+        let size = decoder.size();
+        let mut row = [Rgb888::WHITE; WIDTH];
+        let visible = WIDTH
+            .saturating_sub(x)
+            .min(size.width.try_into().unwrap_or(usize::MAX));
+
+        let rows = HEIGHT
+            .saturating_sub(y)
+            .min(size.height.try_into().unwrap_or(usize::MAX));
+
+        for row_y in 0..rows {
+            decoder.read_row(&mut row[..visible])?;
+            for (row_x, color) in row[..visible].iter().enumerate() {
+                let color = quantizer.quantize(x + row_x, y + row_y, *color);
+                // cannot fail, the row has been clipped to the panel
+                let _ = self.set_pixel(x + row_x, y + row_y, color);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Blends `value` with `alpha` over a white background, like paper.
+fn over_white(value: u8, alpha: u8) -> u8 {
+    let value = u16::from(value) * u16::from(alpha) + 255 * u16::from(255 - alpha);
+    #[allow(clippy::cast_possible_truncation)]
+    let value = (value / 255) as u8;
+    value
+}
@@ -0,0 +1,340 @@
+//! Streaming PNG decoder for non-interlaced images up to [`WIDTH`] pixels wide.
+//!
+//! Image data is inflated through a 32KiB window and unfiltered one scanline at a time.  The
+//! decoder holds roughly 53KiB of state, so it may be better placed in a `static` than on the
+//! stack.  Transparent pixels are blended over white.
+
+use super::{over_white, Decoder};
+use crate::WIDTH;
+use embedded_graphics_core::{pixelcolor::Rgb888, prelude::Size};
+use miniz_oxide::inflate::{
+    core::{decompress, inflate_flags, DecompressorOxide},
+    TINFLStatus,
+};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const WINDOW_SIZE: usize = 32 * 1024;
+/// Widest scanline: RGBA with 16 bits per sample.
+const MAX_SCANLINE: usize = WIDTH * 8;
+
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The data does not start with a PNG signature.
+    InvalidSignature,
+    /// IHDR is missing or malformed.
+    InvalidHeader,
+    /// Interlaced, wider than [`WIDTH`] or an invalid combination of bit depth and color type.
+    Unsupported,
+    /// The data ended before all rows were decoded.
+    Truncated,
+    /// The compressed image data is corrupt.
+    Inflate,
+    /// A scanline uses an unknown filter type.
+    InvalidFilter,
+}
+
+struct Chunk<'a> {
+    kind: &'a [u8],
+    data: &'a [u8],
+    end: usize,
+}
+
+fn chunk(data: &[u8], offset: usize) -> Result<Chunk<'_>, Error> {
+    let start = offset.checked_add(8).ok_or(Error::Truncated)?;
+    let header = data.get(offset..start).ok_or(Error::Truncated)?;
+    let len: usize = u32::from_be_bytes([header[0], header[1], header[2], header[3]])
+        .try_into()
+        .map_err(|_| Error::Truncated)?;
+    let data_end = start.checked_add(len).ok_or(Error::Truncated)?;
+    Ok(Chunk {
+        kind: &header[4..],
+        data: data.get(start..data_end).ok_or(Error::Truncated)?,
+        // skip CRC
+        end: data_end.checked_add(4).ok_or(Error::Truncated)?,
+    })
+}
+
+/// Image data spread over IDAT chunks, inflated through a window.
+struct Inflater<'a> {
+    data: &'a [u8],
+    next_chunk: usize,
+    idat: &'a [u8],
+    decompressor: DecompressorOxide,
+    window: [u8; WINDOW_SIZE],
+    window_position: usize,
+    available: usize,
+}
+
+impl Inflater<'_> {
+    /// Moves on to the next IDAT chunk, returning `false` if there is none.
+    fn next_idat(&mut self) -> Result<bool, Error> {
+        let chunk = chunk(self.data, self.next_chunk)?;
+        if chunk.kind != b"IDAT" {
+            return Ok(false);
+        }
+        self.idat = chunk.data;
+        self.next_chunk = chunk.end;
+        Ok(true)
+    }
+
+    /// Inflates more data into the window.
+    fn inflate(&mut self) -> Result<(), Error> {
+        loop {
+            let (status, consumed, written) = decompress(
+                &mut self.decompressor,
+                self.idat,
+                &mut self.window,
+                self.window_position,
+                inflate_flags::TINFL_FLAG_PARSE_ZLIB_HEADER
+                    | inflate_flags::TINFL_FLAG_HAS_MORE_INPUT,
+            );
+            self.idat = &self.idat[consumed..];
+            self.available = written;
+
+            match status {
+                TINFLStatus::HasMoreOutput | TINFLStatus::NeedsMoreInput | TINFLStatus::Done
+                    if written > 0 =>
+                {
+                    return Ok(())
+                }
+                TINFLStatus::NeedsMoreInput => {
+                    if !self.next_idat()? {
+                        return Err(Error::Truncated);
+                    }
+                }
+                TINFLStatus::Done => return Err(Error::Truncated),
+                _ => return Err(Error::Inflate),
+            }
+        }
+    }
+
+    /// Fills `buffer` with inflated bytes.
+    fn read(&mut self, buffer: &mut [u8]) -> Result<(), Error> {
+        let mut filled = 0;
+        while filled < buffer.len() {
+            if self.available == 0 {
+                self.inflate()?;
+            }
+            let len = self.available.min(buffer.len() - filled);
+            buffer[filled..filled + len]
+                .copy_from_slice(&self.window[self.window_position..self.window_position + len]);
+            self.window_position = (self.window_position + len) & (WINDOW_SIZE - 1);
+            self.available -= len;
+            filled += len;
+        }
+        Ok(())
+    }
+}
+
+/// Layout of pixels within a scanline.
+struct Format<'a> {
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: u8,
+    palette: &'a [u8],
+}
+
+impl Format<'_> {
+    fn channels(&self) -> usize {
+        match self.color_type {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        self.channels() * usize::from(self.bit_depth)
+    }
+
+    /// Sample `channel` of pixel `x`, scaled to 8 bits unless the image is palette based.
+    fn sample(&self, scanline: &[u8], x: usize, channel: usize) -> u8 {
+        let depth = usize::from(self.bit_depth);
+        match depth {
+            16 => scanline[(x * self.channels() + channel) * 2],
+            8 => scanline[x * self.channels() + channel],
+            _ => {
+                let bit = x * depth;
+                let max = (1u8 << depth) - 1;
+                let value = (scanline[bit / 8] >> (8 - depth - bit % 8)) & max;
+                if self.color_type == 3 {
+                    value
+                } else {
+                    value * (255 / max)
+                }
+            }
+        }
+    }
+
+    fn pixel(&self, scanline: &[u8], x: usize) -> Rgb888 {
+        match self.color_type {
+            0 => {
+                let gray = self.sample(scanline, x, 0);
+                Rgb888::new(gray, gray, gray)
+            }
+            2 => Rgb888::new(
+                self.sample(scanline, x, 0),
+                self.sample(scanline, x, 1),
+                self.sample(scanline, x, 2),
+            ),
+            3 => {
+                let index = usize::from(self.sample(scanline, x, 0)) * 3;
+                match self.palette.get(index..index + 3) {
+                    Some(rgb) => Rgb888::new(rgb[0], rgb[1], rgb[2]),
+                    None => Rgb888::new(0, 0, 0),
+                }
+            }
+            4 => {
+                let gray = over_white(self.sample(scanline, x, 0), self.sample(scanline, x, 1));
+                Rgb888::new(gray, gray, gray)
+            }
+            _ => {
+                let alpha = self.sample(scanline, x, 3);
+                Rgb888::new(
+                    over_white(self.sample(scanline, x, 0), alpha),
+                    over_white(self.sample(scanline, x, 1), alpha),
+                    over_white(self.sample(scanline, x, 2), alpha),
+                )
+            }
+        }
+    }
+}
+
+/// Decodes PNG images from a byte slice, one row at a time.
+pub struct PngDecoder<'a> {
+    inflater: Inflater<'a>,
+    format: Format<'a>,
+    scanlines: [[u8; MAX_SCANLINE]; 2],
+    current: usize,
+}
+
+impl<'a> PngDecoder<'a> {
+    /// Parses the chunks of `data` up to the first IDAT.
+    ///
+    /// # Errors
+    ///
+    /// [`Error`] if `data` is not a PNG image this decoder supports.
+    pub fn new(data: &'a [u8]) -> Result<Self, Error> {
+        if data.get(..8) != Some(&SIGNATURE[..]) {
+            return Err(Error::InvalidSignature);
+        }
+
+        let header = chunk(data, 8)?;
+        if header.kind != b"IHDR" || header.data.len() != 13 {
+            return Err(Error::InvalidHeader);
+        }
+        let ihdr = header.data;
+        let mut format = Format {
+            width: u32::from_be_bytes([ihdr[0], ihdr[1], ihdr[2], ihdr[3]]),
+            height: u32::from_be_bytes([ihdr[4], ihdr[5], ihdr[6], ihdr[7]]),
+            bit_depth: ihdr[8],
+            color_type: ihdr[9],
+            palette: &[],
+        };
+
+        let valid = matches!(
+            (format.color_type, format.bit_depth),
+            (0, 1 | 2 | 4 | 8 | 16) | (3, 1 | 2 | 4 | 8) | (2 | 4 | 6, 8 | 16)
+        );
+        let interlaced = ihdr[12] != 0;
+        if !valid || interlaced || usize::try_from(format.width).map_or(true, |w| w > WIDTH) {
+            return Err(Error::Unsupported);
+        }
+
+        let mut inflater = Inflater {
+            data,
+            next_chunk: header.end,
+            idat: &[],
+            decompressor: DecompressorOxide::new(),
+            window: [0; WINDOW_SIZE],
+            window_position: 0,
+            available: 0,
+        };
+
+        loop {
+            let chunk = chunk(data, inflater.next_chunk)?;
+            match chunk.kind {
+                b"PLTE" => format.palette = chunk.data,
+                b"IDAT" => break,
+                b"IEND" => return Err(Error::Truncated),
+                _ => {}
+            }
+            inflater.next_chunk = chunk.end;
+        }
+
+        Ok(Self {
+            inflater,
+            format,
+            scanlines: [[0; MAX_SCANLINE]; 2],
+            current: 0,
+        })
+    }
+}
+
+impl Decoder for PngDecoder<'_> {
+    type Error = Error;
+
+    fn size(&self) -> Size {
+        Size::new(self.format.width, self.format.height)
+    }
+
+    fn read_row(&mut self, row: &mut [Rgb888]) -> Result<(), Error> {
+        let width = usize::try_from(self.format.width).map_err(|_| Error::Unsupported)?;
+        let len = (width * self.format.bits_per_pixel()).div_ceil(8);
+        let left = (self.format.bits_per_pixel() / 8).max(1);
+
+        let mut filter = [0];
+        self.inflater.read(&mut filter)?;
+
+        self.current ^= 1;
+        let [first, second] = &mut self.scanlines;
+        let (scanline, previous) = if self.current == 0 {
+            (&mut first[..len], &second[..len])
+        } else {
+            (&mut second[..len], &first[..len])
+        };
+        self.inflater.read(scanline)?;
+        unfilter(filter[0], scanline, previous, left)?;
+
+        for (x, color) in row.iter_mut().take(width).enumerate() {
+            *color = self.format.pixel(scanline, x);
+        }
+        Ok(())
+    }
+}
+
+fn unfilter(filter: u8, scanline: &mut [u8], previous: &[u8], left: usize) -> Result<(), Error> {
+    for i in 0..scanline.len() {
+        let a = if i >= left { scanline[i - left] } else { 0 };
+        let b = previous[i];
+        let c = if i >= left { previous[i - left] } else { 0 };
+        let predictor = match filter {
+            0 => 0,
+            1 => a,
+            2 => b,
+            #[allow(clippy::cast_possible_truncation)]
+            3 => ((u16::from(a) + u16::from(b)) / 2) as u8,
+            4 => paeth(a, b, c),
+            _ => return Err(Error::InvalidFilter),
+        };
+        scanline[i] = scanline[i].wrapping_add(predictor);
+    }
+    Ok(())
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = i16::from(a) + i16::from(b) - i16::from(c);
+    let pa = (p - i16::from(a)).abs();
+    let pb = (p - i16::from(b)).abs();
+    let pc = (p - i16::from(c)).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
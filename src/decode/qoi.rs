@@ -0,0 +1,137 @@
+//! Streaming [QOI](https://qoiformat.org/) decoder.
+
+use super::{over_white, Decoder};
+use embedded_graphics_core::{pixelcolor::Rgb888, prelude::Size};
+
+const HEADER_LEN: usize = 14;
+
+const OP_INDEX: u8 = 0b0000_0000;
+const OP_DIFF: u8 = 0b0100_0000;
+const OP_LUMA: u8 = 0b1000_0000;
+const OP_RGB: u8 = 0b1111_1110;
+const OP_RGBA: u8 = 0b1111_1111;
+const MASK: u8 = 0b1100_0000;
+
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The data does not start with a QOI header.
+    InvalidHeader,
+    /// The data ended before all pixels were decoded.
+    Truncated,
+}
+
+/// Decodes QOI images from a byte slice, one row at a time.
+pub struct QoiDecoder<'a> {
+    data: &'a [u8],
+    position: usize,
+    width: u32,
+    height: u32,
+    index: [[u8; 4]; 64],
+    pixel: [u8; 4],
+    run: u8,
+}
+
+impl<'a> QoiDecoder<'a> {
+    /// Parses the QOI header at the start of `data`.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::InvalidHeader`] if `data` is not a QOI image.
+    pub fn new(data: &'a [u8]) -> Result<Self, Error> {
+        let header = data.get(..HEADER_LEN).ok_or(Error::InvalidHeader)?;
+        if &header[..4] != b"qoif" {
+            return Err(Error::InvalidHeader);
+        }
+
+        Ok(Self {
+            data,
+            position: HEADER_LEN,
+            width: u32::from_be_bytes([header[4], header[5], header[6], header[7]]),
+            height: u32::from_be_bytes([header[8], header[9], header[10], header[11]]),
+            index: [[0; 4]; 64],
+            pixel: [0, 0, 0, 255],
+            run: 0,
+        })
+    }
+
+    fn byte(&mut self) -> Result<u8, Error> {
+        let byte = *self.data.get(self.position).ok_or(Error::Truncated)?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn next_pixel(&mut self) -> Result<Rgb888, Error> {
+        if self.run > 0 {
+            self.run -= 1;
+        } else {
+            let op = self.byte()?;
+            match op {
+                OP_RGB => {
+                    self.pixel[0] = self.byte()?;
+                    self.pixel[1] = self.byte()?;
+                    self.pixel[2] = self.byte()?;
+                }
+                OP_RGBA => {
+                    self.pixel[0] = self.byte()?;
+                    self.pixel[1] = self.byte()?;
+                    self.pixel[2] = self.byte()?;
+                    self.pixel[3] = self.byte()?;
+                }
+                _ => match op & MASK {
+                    OP_INDEX => self.pixel = self.index[usize::from(op)],
+                    OP_DIFF => {
+                        self.pixel[0] =
+                            self.pixel[0].wrapping_add((op >> 4) & 0x03).wrapping_sub(2);
+                        self.pixel[1] =
+                            self.pixel[1].wrapping_add((op >> 2) & 0x03).wrapping_sub(2);
+                        self.pixel[2] = self.pixel[2].wrapping_add(op & 0x03).wrapping_sub(2);
+                    }
+                    OP_LUMA => {
+                        let green = (op & 0x3f).wrapping_sub(32);
+                        let byte = self.byte()?;
+                        self.pixel[0] = self.pixel[0]
+                            .wrapping_add(green)
+                            .wrapping_add(byte >> 4)
+                            .wrapping_sub(8);
+                        self.pixel[1] = self.pixel[1].wrapping_add(green);
+                        self.pixel[2] = self.pixel[2]
+                            .wrapping_add(green)
+                            .wrapping_add(byte & 0x0f)
+                            .wrapping_sub(8);
+                    }
+                    // 0b11xx_xxxx, a run with a bias of -1
+                    _ => self.run = op & 0x3f,
+                },
+            }
+
+            let [r, g, b, a] = self.pixel.map(usize::from);
+            self.index[(r * 3 + g * 5 + b * 7 + a * 11) % 64] = self.pixel;
+        }
+
+        let [r, g, b, a] = self.pixel;
+        Ok(Rgb888::new(
+            over_white(r, a),
+            over_white(g, a),
+            over_white(b, a),
+        ))
+    }
+}
+
+impl Decoder for QoiDecoder<'_> {
+    type Error = Error;
+
+    fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+
+    fn read_row(&mut self, row: &mut [Rgb888]) -> Result<(), Error> {
+        for x in 0..self.width {
+            let pixel = self.next_pixel()?;
+            if let Some(color) = usize::try_from(x).ok().and_then(|x| row.get_mut(x)) {
+                *color = pixel;
+            }
+        }
+        Ok(())
+    }
+}
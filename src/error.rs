@@ -1,4 +1,9 @@
+use core::fmt;
+use embedded_hal::{digital, spi};
+
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub enum Error<BUSY, RST, DC, S>
 where
     BUSY: embedded_hal::digital::Error,
@@ -11,4 +16,204 @@ where
     ResetPin(RST),
     Spi(S),
     PixelOutOfBounds,
+    /// BUSY did not change within [`crate::timing::Timings::busy_timeout_us`].
+    BusyTimeout,
+    /// The low-voltage detector tripped, so the refresh was refused.  See
+    /// [`crate::config::PanelConfig::refuse_low_power`].
+    LowPower,
+    /// The controller did not receive a whole frame.  See
+    /// [`crate::config::PanelConfig::verify_transfer`].
+    TransferIncomplete,
+    /// The interface cannot read from the controller.  See
+    /// [`crate::interface::Interface::read_data`].
+    ReadUnsupported,
+}
+
+impl<BUSY, RST, DC, S> Error<BUSY, RST, DC, S>
+where
+    BUSY: embedded_hal::digital::Error,
+    RST: embedded_hal::digital::Error,
+    DC: embedded_hal::digital::Error,
+    S: embedded_hal::spi::Error,
+{
+    pub fn kind(&self) -> ErrorKind {
+        ErasedError::from(self).kind()
+    }
+}
+
+impl<BUSY, RST, DC, S> fmt::Display for Error<BUSY, RST, DC, S>
+where
+    BUSY: embedded_hal::digital::Error,
+    RST: embedded_hal::digital::Error,
+    DC: embedded_hal::digital::Error,
+    S: embedded_hal::spi::Error,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        ErasedError::from(self).fmt(f)
+    }
+}
+
+impl<BUSY, RST, DC, S> core::error::Error for Error<BUSY, RST, DC, S>
+where
+    BUSY: embedded_hal::digital::Error,
+    RST: embedded_hal::digital::Error,
+    DC: embedded_hal::digital::Error,
+    S: embedded_hal::spi::Error,
+{
+}
+
+/// [`Error`] with HAL errors reduced to their [`embedded_hal`] error kinds.  Any [`Error`]
+/// converts into this, so application code can use `?` without naming HAL error types.
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ErasedError {
+    BusyPin(digital::ErrorKind),
+    DataCommandPin(digital::ErrorKind),
+    ResetPin(digital::ErrorKind),
+    Spi(spi::ErrorKind),
+    PixelOutOfBounds,
+    BusyTimeout,
+    LowPower,
+    TransferIncomplete,
+    ReadUnsupported,
+}
+
+impl ErasedError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::BusyPin(_) | Self::DataCommandPin(_) | Self::ResetPin(_) => ErrorKind::Pin,
+            Self::Spi(_) => ErrorKind::Bus,
+            Self::TransferIncomplete | Self::ReadUnsupported => ErrorKind::InvalidState,
+            Self::PixelOutOfBounds => ErrorKind::OutOfBounds,
+            Self::BusyTimeout => ErrorKind::Timeout,
+            Self::LowPower => ErrorKind::LowPower,
+        }
+    }
+}
+
+impl<BUSY, RST, DC, S> From<&Error<BUSY, RST, DC, S>> for ErasedError
+where
+    BUSY: embedded_hal::digital::Error,
+    RST: embedded_hal::digital::Error,
+    DC: embedded_hal::digital::Error,
+    S: embedded_hal::spi::Error,
+{
+    fn from(error: &Error<BUSY, RST, DC, S>) -> Self {
+        match error {
+            Error::BusyPin(e) => Self::BusyPin(e.kind()),
+            Error::DataCommandPin(e) => Self::DataCommandPin(e.kind()),
+            Error::ResetPin(e) => Self::ResetPin(e.kind()),
+            Error::Spi(e) => Self::Spi(e.kind()),
+            Error::PixelOutOfBounds => Self::PixelOutOfBounds,
+            Error::BusyTimeout => Self::BusyTimeout,
+            Error::LowPower => Self::LowPower,
+            Error::TransferIncomplete => Self::TransferIncomplete,
+            Error::ReadUnsupported => Self::ReadUnsupported,
+        }
+    }
+}
+
+impl<BUSY, RST, DC, S> From<Error<BUSY, RST, DC, S>> for ErasedError
+where
+    BUSY: embedded_hal::digital::Error,
+    RST: embedded_hal::digital::Error,
+    DC: embedded_hal::digital::Error,
+    S: embedded_hal::spi::Error,
+{
+    fn from(error: Error<BUSY, RST, DC, S>) -> Self {
+        Self::from(&error)
+    }
+}
+
+impl fmt::Display for ErasedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BusyPin(kind) => write!(f, "BUSY pin error: {kind}"),
+            Self::DataCommandPin(kind) => write!(f, "DC pin error: {kind}"),
+            Self::ResetPin(kind) => write!(f, "RST pin error: {kind}"),
+            Self::Spi(kind) => write!(f, "SPI error: {kind}"),
+            Self::PixelOutOfBounds => f.write_str("pixel out of bounds"),
+            Self::BusyTimeout => f.write_str("timed out waiting on BUSY"),
+            Self::LowPower => f.write_str("supply voltage too low to refresh"),
+            Self::TransferIncomplete => f.write_str("frame transfer incomplete"),
+            Self::ReadUnsupported => f.write_str("interface cannot read from the controller"),
+        }
+    }
+}
+
+impl core::error::Error for ErasedError {}
+
+/// An [`Error`] from changing the power state of a [`crate::Display`], along with the
+/// [`crate::Display`] in the state it was in before.  It can be retried, or its resources
+/// recovered with [`crate::Display::release`].
+pub struct TransitionError<T, E> {
+    pub display: T,
+    pub error: E,
+}
+
+impl<T, E> fmt::Debug for TransitionError<T, E>
+where
+    E: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransitionError")
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, E> fmt::Display for TransitionError<T, E>
+where
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl<T, E> core::error::Error for TransitionError<T, E> where E: core::error::Error {}
+
+impl<T, BUSY, RST, DC, S> From<TransitionError<T, Error<BUSY, RST, DC, S>>> for ErasedError
+where
+    BUSY: embedded_hal::digital::Error,
+    RST: embedded_hal::digital::Error,
+    DC: embedded_hal::digital::Error,
+    S: embedded_hal::spi::Error,
+{
+    fn from(error: TransitionError<T, Error<BUSY, RST, DC, S>>) -> Self {
+        Self::from(&error.error)
+    }
+}
+
+/// Coarse category of an [`Error`].
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The SPI bus failed.
+    Bus,
+    /// A GPIO pin failed.
+    Pin,
+    /// The controller did not respond in time.
+    Timeout,
+    /// Co-ordinates were outside the panel.
+    OutOfBounds,
+    /// The controller or interface was in a state that does not allow the operation.
+    InvalidState,
+    /// The supply voltage was too low.
+    LowPower,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Bus => "bus error",
+            Self::Pin => "pin error",
+            Self::Timeout => "timeout",
+            Self::OutOfBounds => "out of bounds",
+            Self::InvalidState => "invalid state",
+            Self::LowPower => "low power",
+        })
+    }
 }
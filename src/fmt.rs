@@ -0,0 +1,30 @@
+#![macro_use]
+#![allow(unused_macros)]
+
+// `defmt` takes precedence when both features are enabled.
+
+macro_rules! trace {
+    ($s:literal $(, $x:expr)* $(,)?) => {
+        {
+            #[cfg(all(feature = "log", not(feature = "defmt")))]
+            ::log::trace!($s $(, $x)*);
+            #[cfg(feature = "defmt")]
+            ::defmt::trace!($s $(, $x)*);
+            #[cfg(not(any(feature = "log", feature = "defmt")))]
+            let _ = ($( & $x ),*);
+        }
+    };
+}
+
+macro_rules! debug {
+    ($s:literal $(, $x:expr)* $(,)?) => {
+        {
+            #[cfg(all(feature = "log", not(feature = "defmt")))]
+            ::log::debug!($s $(, $x)*);
+            #[cfg(feature = "defmt")]
+            ::defmt::debug!($s $(, $x)*);
+            #[cfg(not(any(feature = "log", feature = "defmt")))]
+            let _ = ($( & $x ),*);
+        }
+    };
+}
@@ -1,38 +1,49 @@
-use crate::{color::Color, Display, HEIGHT, WIDTH};
+use crate::{
+    color::Color,
+    interface::Interface,
+    packed::{Canvas, PackedImage},
+    state::PowerState,
+    Display, DisplayError, HEIGHT, WIDTH,
+};
+use core::convert::Infallible;
 use embedded_graphics_core::{
     draw_target::DrawTarget,
-    pixelcolor::{raw::RawU4, PixelColor, Rgb888, RgbColor},
-    prelude::{OriginDimensions, RawData, Size},
+    image::{GetPixel, ImageDrawable},
+    pixelcolor::{
+        raw::RawU4, BinaryColor, Gray2, Gray4, Gray8, GrayColor, PixelColor, Rgb555, Rgb565,
+        Rgb888, RgbColor,
+    },
+    prelude::{Dimensions, OriginDimensions, Point, RawData, Size},
+    primitives::{PointsIter, Rectangle},
     Pixel,
 };
 use embedded_hal::{
     delay::DelayNs,
     digital::{InputPin, OutputPin},
-    spi::SpiDevice,
 };
 
-impl<D, S, RST, DC, BUSY> OriginDimensions for Display<D, S, RST, DC, BUSY>
+impl<D, DI, RST, BUSY, STATE> OriginDimensions for Display<D, DI, RST, BUSY, STATE>
 where
     D: DelayNs,
-    S: SpiDevice,
+    DI: Interface,
     RST: OutputPin,
-    DC: OutputPin,
     BUSY: InputPin,
+    STATE: PowerState,
 {
     fn size(&self) -> Size {
         Size::new(WIDTH.try_into().unwrap(), HEIGHT.try_into().unwrap())
     }
 }
-impl<D, S, RST, DC, BUSY> DrawTarget for Display<D, S, RST, DC, BUSY>
+impl<D, DI, RST, BUSY, STATE> DrawTarget for Display<D, DI, RST, BUSY, STATE>
 where
     D: DelayNs,
-    S: SpiDevice,
+    DI: Interface,
     RST: OutputPin,
-    DC: OutputPin,
     BUSY: InputPin,
+    STATE: PowerState,
 {
     type Color = Color;
-    type Error = crate::error::Error<BUSY::Error, RST::Error, DC::Error, S::Error>;
+    type Error = DisplayError<BUSY, RST, DI>;
 
     fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
     where
@@ -50,6 +61,88 @@ where
     }
 }
 
+impl<B> OriginDimensions for Canvas<B>
+where
+    B: AsRef<[u8]>,
+{
+    fn size(&self) -> Size {
+        Size::new(
+            self.width().try_into().unwrap(),
+            self.height().try_into().unwrap(),
+        )
+    }
+}
+
+/// Pixels outside the canvas are discarded.
+impl<B> DrawTarget for Canvas<B>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    type Color = Color;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let (Ok(x), Ok(y)) = (point.x.try_into(), point.y.try_into()) {
+                self.set_pixel(x, y, color);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        Canvas::clear(self, color);
+        Ok(())
+    }
+}
+
+impl OriginDimensions for PackedImage<'_> {
+    fn size(&self) -> Size {
+        Size::new(
+            self.width().try_into().unwrap(),
+            self.height().try_into().unwrap(),
+        )
+    }
+}
+
+/// Draws through [`DrawTarget::fill_contiguous`], pixel by pixel on a [`Display`].  Use
+/// [`Display::draw_packed`] to copy whole rows instead.
+impl ImageDrawable for PackedImage<'_> {
+    type Color = Color;
+
+    fn draw<T>(&self, target: &mut T) -> Result<(), T::Error>
+    where
+        T: DrawTarget<Color = Self::Color>,
+    {
+        self.draw_sub_image(target, &self.bounding_box())
+    }
+
+    fn draw_sub_image<T>(&self, target: &mut T, area: &Rectangle) -> Result<(), T::Error>
+    where
+        T: DrawTarget<Color = Self::Color>,
+    {
+        let visible = area.intersection(&self.bounding_box());
+        target.fill_contiguous(
+            &Rectangle::new(visible.top_left - area.top_left, visible.size),
+            visible
+                .points()
+                .map(|point| GetPixel::pixel(self, point).unwrap_or_default()),
+        )
+    }
+}
+
+impl GetPixel for PackedImage<'_> {
+    type Color = Color;
+
+    fn pixel(&self, point: Point) -> Option<Self::Color> {
+        PackedImage::pixel(self, point.x.try_into().ok()?, point.y.try_into().ok()?)
+    }
+}
+
 impl PixelColor for Color {
     type Raw = RawU4;
 }
@@ -79,6 +172,52 @@ impl From<Rgb888> for Color {
     }
 }
 
+impl From<Rgb565> for Color {
+    fn from(color: Rgb565) -> Self {
+        Rgb888::from(color).into()
+    }
+}
+
+impl From<Rgb555> for Color {
+    fn from(color: Rgb555) -> Self {
+        Rgb888::from(color).into()
+    }
+}
+
+/// Grays are thresholded to black or white, as the nearest ink to mid-grays is orange.
+impl From<Gray8> for Color {
+    fn from(color: Gray8) -> Self {
+        if color.luma() >= 0x80 {
+            Color::WHITE
+        } else {
+            Color::BLACK
+        }
+    }
+}
+
+impl From<Gray4> for Color {
+    fn from(color: Gray4) -> Self {
+        Gray8::from(color).into()
+    }
+}
+
+impl From<Gray2> for Color {
+    fn from(color: Gray2) -> Self {
+        Gray8::from(color).into()
+    }
+}
+
+/// [`BinaryColor::On`] is black ink on white paper.  Use [`crate::convert::Converted::with_map`]
+/// for other mappings.
+impl From<BinaryColor> for Color {
+    fn from(color: BinaryColor) -> Self {
+        match color {
+            BinaryColor::On => Color::BLACK,
+            BinaryColor::Off => Color::WHITE,
+        }
+    }
+}
+
 impl From<Color> for Rgb888 {
     fn from(color: Color) -> Self {
         let (display, _) = RGB_DISPLAY_PAIRS
@@ -0,0 +1,356 @@
+//! Support for the rest of the Inkplate 6 COLOR board.
+//!
+//! The panel is wired to the ESP32 as follows.  Pin types are left to the HAL, so
+//! [`display`] only documents which pin goes where.
+//!
+//! | Panel | ESP32  |
+//! |-------|--------|
+//! | RST   | GPIO19 |
+//! | DC    | GPIO33 |
+//! | BUSY  | GPIO32 |
+//! | CS    | GPIO27 |
+//! | SCK   | GPIO18 |
+//! | MOSI  | GPIO23 |
+//!
+//! There is no MISO, so the panel cannot be read from.  Leave
+//! [`crate::config::PanelConfig::refuse_low_power`] and
+//! [`crate::config::PanelConfig::verify_transfer`] off and do not call
+//! [`crate::Display::measure_vcom`] or [`crate::Display::low_power_detected`].
+//!
+//! The MCP23017 I/O expander and the PCF85063A real-time clock share the board's I2C bus.  Use
+//! e.g. `embedded-hal-bus` to give both drivers access to it.
+
+use crate::{interface::FourWire, Display};
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{InputPin, OutputPin},
+    i2c::I2c,
+    spi::SpiDevice,
+};
+
+/// Panel reset, held low to reset the controller.
+pub const RST_GPIO: u8 = 19;
+/// Panel data/command select, low for commands and high for data.
+pub const DC_GPIO: u8 = 33;
+/// Panel busy flag, low while the controller is busy.
+pub const BUSY_GPIO: u8 = 32;
+/// Panel SPI chip select.
+pub const CS_GPIO: u8 = 27;
+/// Panel SPI clock.
+pub const SCK_GPIO: u8 = 18;
+/// Panel SPI data, from the ESP32 to the panel.
+pub const MOSI_GPIO: u8 = 23;
+/// ADC input for battery voltage, through a 1:2 divider.
+pub const BATTERY_GPIO: u8 = 35;
+/// Driven low by the RTC when its alarm fires, for use as a deep sleep wake source.
+pub const RTC_INTERRUPT_GPIO: u8 = 39;
+
+/// Creates a [`Display`] for the panel from the board's pins:
+///
+/// - `spi` must use [`CS_GPIO`] for CS, [`SCK_GPIO`] for SCK and [`MOSI_GPIO`] for MOSI, with no
+///   MISO
+/// - `rst` is [`RST_GPIO`], as an output
+/// - `dc` is [`DC_GPIO`], as an output
+/// - `busy` is [`BUSY_GPIO`], as an input
+pub fn display<D, S, RST, DC, BUSY>(
+    spi: S,
+    rst: RST,
+    dc: DC,
+    busy: BUSY,
+    delay: D,
+) -> Display<D, FourWire<S, DC>, RST, BUSY>
+where
+    D: DelayNs,
+    S: SpiDevice,
+    RST: OutputPin,
+    DC: OutputPin,
+    BUSY: InputPin,
+{
+    Display::new(spi, rst, dc, busy, delay)
+}
+
+/// Pins of the [`IoExpander`] used by the board.  Pins 0 to 7 are port A and 8 to 15 are
+/// port B.
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExpanderPins {
+    /// Switches the battery voltage divider on, active high.
+    pub battery_enable: u8,
+    /// Switches the panel supply on, active high.
+    pub panel_power: u8,
+}
+
+impl Default for ExpanderPins {
+    fn default() -> Self {
+        Self {
+            battery_enable: 9,
+            panel_power: 10,
+        }
+    }
+}
+
+/// The board's internal MCP23017 I/O expander.
+pub struct IoExpander<I2C> {
+    i2c: I2C,
+    pins: ExpanderPins,
+    /// Shadow copies of IODIR and OLAT, port B in the high byte.
+    direction: u16,
+    latch: u16,
+}
+
+impl<I2C> IoExpander<I2C>
+where
+    I2C: I2c,
+{
+    pub const ADDRESS: u8 = 0x20;
+
+    const IODIRA: u8 = 0x00;
+    const OLATA: u8 = 0x14;
+
+    /// Creates a driver for an expander in its power-on state, i.e. with every pin an input.
+    pub fn new(i2c: I2C, pins: ExpanderPins) -> Self {
+        Self {
+            i2c,
+            pins,
+            direction: 0xffff,
+            latch: 0,
+        }
+    }
+
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+
+    /// Makes `pin` an output driven to `high`.
+    ///
+    /// # Errors
+    ///
+    /// I2C errors are passed through.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pin` is not less than 16.
+    pub fn set_output(&mut self, pin: u8, high: bool) -> Result<(), I2C::Error> {
+        assert!(pin < 16);
+        let mask = 1 << pin;
+        if high {
+            self.latch |= mask;
+        } else {
+            self.latch &= !mask;
+        }
+        self.write_pair(Self::OLATA, self.latch)?;
+
+        if self.direction & mask != 0 {
+            self.direction &= !mask;
+            self.write_pair(Self::IODIRA, self.direction)?;
+        }
+        Ok(())
+    }
+
+    /// Switches the panel supply.
+    ///
+    /// # Errors
+    ///
+    /// I2C errors are passed through.
+    pub fn set_panel_power(&mut self, on: bool) -> Result<(), I2C::Error> {
+        self.set_output(self.pins.panel_power, on)
+    }
+
+    /// Measures the battery in millivolts.  `read_mv` must return the voltage at
+    /// [`BATTERY_GPIO`] in millivolts, as read with the HAL's ADC driver.
+    ///
+    /// # Errors
+    ///
+    /// I2C errors are passed through.
+    pub fn battery_mv<D, F>(&mut self, delay: &mut D, read_mv: F) -> Result<u32, I2C::Error>
+    where
+        D: DelayNs,
+        F: FnOnce() -> u32,
+    {
+        self.set_output(self.pins.battery_enable, true)?;
+        // let the divider settle
+        delay.delay_ms(1);
+        let mv = read_mv();
+        self.set_output(self.pins.battery_enable, false)?;
+        Ok(mv * 2)
+    }
+
+    /// Writes `value` to the port A register `register` and its port B twin.
+    fn write_pair(&mut self, register: u8, value: u16) -> Result<(), I2C::Error> {
+        let [a, b] = value.to_le_bytes();
+        self.i2c.write(Self::ADDRESS, &[register, a, b])
+    }
+}
+
+/// Time of day and date as kept by the [`Rtc`], in 24-hour time.
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DateTime {
+    /// 0 to 99, for 2000 to 2099.
+    pub year: u8,
+    pub month: u8,
+    pub day: u8,
+    /// 0 to 6, 0 being Sunday.
+    pub weekday: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+/// When the [`Rtc`] alarm fires.  Fields that are `None` are ignored, so e.g. only setting
+/// `minute` fires once an hour.
+#[derive(Default, Debug, Eq, Hash, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Alarm {
+    pub second: Option<u8>,
+    pub minute: Option<u8>,
+    pub hour: Option<u8>,
+    pub day: Option<u8>,
+    pub weekday: Option<u8>,
+}
+
+/// The board's PCF85063A real-time clock.
+pub struct Rtc<I2C> {
+    i2c: I2C,
+}
+
+impl<I2C> Rtc<I2C>
+where
+    I2C: I2c,
+{
+    pub const ADDRESS: u8 = 0x51;
+
+    const CONTROL_2: u8 = 0x01;
+    const SECONDS: u8 = 0x04;
+    const SECOND_ALARM: u8 = 0x0b;
+
+    /// Alarm interrupt enable, in `CONTROL_2`.
+    const AIE: u8 = 1 << 7;
+    /// Alarm flag, in `CONTROL_2`.
+    const AF: u8 = 1 << 6;
+    /// Set in an alarm register to leave that field out of the alarm.
+    const ALARM_DISABLED: u8 = 1 << 7;
+
+    pub fn new(i2c: I2C) -> Self {
+        Self { i2c }
+    }
+
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+
+    /// # Errors
+    ///
+    /// I2C errors are passed through.
+    pub fn time(&mut self) -> Result<DateTime, I2C::Error> {
+        let mut registers = [0; 7];
+        self.i2c
+            .write_read(Self::ADDRESS, &[Self::SECONDS], &mut registers)?;
+        Ok(DateTime {
+            // bit 7 of seconds is the oscillator stop flag
+            second: from_bcd(registers[0] & 0x7f),
+            minute: from_bcd(registers[1] & 0x7f),
+            hour: from_bcd(registers[2] & 0x3f),
+            day: from_bcd(registers[3] & 0x3f),
+            weekday: registers[4] & 0x07,
+            month: from_bcd(registers[5] & 0x1f),
+            year: from_bcd(registers[6]),
+        })
+    }
+
+    /// # Errors
+    ///
+    /// I2C errors are passed through.
+    pub fn set_time(&mut self, time: &DateTime) -> Result<(), I2C::Error> {
+        self.i2c.write(
+            Self::ADDRESS,
+            &[
+                Self::SECONDS,
+                to_bcd(time.second),
+                to_bcd(time.minute),
+                to_bcd(time.hour),
+                to_bcd(time.day),
+                time.weekday,
+                to_bcd(time.month),
+                to_bcd(time.year),
+            ],
+        )
+    }
+
+    /// Sets the alarm and enables its interrupt on [`RTC_INTERRUPT_GPIO`], clearing any alarm
+    /// that already fired.
+    ///
+    /// # Errors
+    ///
+    /// I2C errors are passed through.
+    pub fn set_alarm(&mut self, alarm: &Alarm) -> Result<(), I2C::Error> {
+        let field = |value: Option<u8>| value.map_or(Self::ALARM_DISABLED, to_bcd);
+        self.i2c.write(
+            Self::ADDRESS,
+            &[
+                Self::SECOND_ALARM,
+                field(alarm.second),
+                field(alarm.minute),
+                field(alarm.hour),
+                field(alarm.day),
+                alarm.weekday.unwrap_or(Self::ALARM_DISABLED),
+            ],
+        )?;
+        self.update_control_2(|control| (control | Self::AIE) & !Self::AF)
+    }
+
+    /// Whether the alarm has fired since it was set or cleared.
+    ///
+    /// # Errors
+    ///
+    /// I2C errors are passed through.
+    pub fn alarm_fired(&mut self) -> Result<bool, I2C::Error> {
+        Ok(self.control_2()? & Self::AF != 0)
+    }
+
+    /// Disables the alarm and its interrupt.
+    ///
+    /// # Errors
+    ///
+    /// I2C errors are passed through.
+    pub fn clear_alarm(&mut self) -> Result<(), I2C::Error> {
+        self.update_control_2(|control| control & !(Self::AIE | Self::AF))?;
+        self.i2c.write(
+            Self::ADDRESS,
+            &[
+                Self::SECOND_ALARM,
+                Self::ALARM_DISABLED,
+                Self::ALARM_DISABLED,
+                Self::ALARM_DISABLED,
+                Self::ALARM_DISABLED,
+                Self::ALARM_DISABLED,
+            ],
+        )
+    }
+
+    fn control_2(&mut self) -> Result<u8, I2C::Error> {
+        let mut control = [0];
+        self.i2c
+            .write_read(Self::ADDRESS, &[Self::CONTROL_2], &mut control)?;
+        Ok(control[0])
+    }
+
+    fn update_control_2<F>(&mut self, update: F) -> Result<(), I2C::Error>
+    where
+        F: FnOnce(u8) -> u8,
+    {
+        let control = update(self.control_2()?);
+        self.i2c.write(Self::ADDRESS, &[Self::CONTROL_2, control])
+    }
+}
+
+fn to_bcd(value: u8) -> u8 {
+    ((value / 10) << 4) | (value % 10)
+}
+
+fn from_bcd(value: u8) -> u8 {
+    (value >> 4) * 10 + (value & 0x0f)
+}
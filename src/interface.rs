@@ -0,0 +1,341 @@
+//! Serial interfaces to the UC8159 controller.
+//!
+//! The controller distinguishes commands from data either with a separate DC pin
+//! ([`FourWire`]) or with a 9th bit sent ahead of every byte ([`ThreeWire`], [`ThreeWirePacked`]).
+//! The 3-wire variants require the panel's BS pin to select 3-wire mode.
+
+use crate::error::Error;
+use core::convert::Infallible;
+use embedded_hal::{
+    digital::{self, OutputPin},
+    spi::{self, SpiDevice},
+};
+
+/// Data bytes sent per SPI transaction by the 3-wire interfaces.  A multiple of 8 so that packed
+/// transactions only ever end in padding after the last byte.
+const CHUNK_SIZE: usize = 64;
+
+/// Sends commands and data to the controller.
+pub trait Interface {
+    type DataCommandError: digital::Error;
+    type SpiError: spi::Error;
+
+    /// Sends a single command byte.
+    ///
+    /// # Errors
+    ///
+    /// HAL errors are wrapped in [`Error::DataCommandPin`] or [`Error::Spi`].
+    fn send_command<BUSY, RST>(
+        &mut self,
+        command: u8,
+    ) -> Result<(), Error<BUSY, RST, Self::DataCommandError, Self::SpiError>>
+    where
+        BUSY: digital::Error,
+        RST: digital::Error;
+
+    /// Sends data bytes following a command.
+    ///
+    /// # Errors
+    ///
+    /// HAL errors are wrapped in [`Error::DataCommandPin`] or [`Error::Spi`].
+    fn send_data<BUSY, RST>(
+        &mut self,
+        data: &[u8],
+    ) -> Result<(), Error<BUSY, RST, Self::DataCommandError, Self::SpiError>>
+    where
+        BUSY: digital::Error,
+        RST: digital::Error;
+
+    /// Reads data bytes following a command.  Interfaces that cannot read keep the default,
+    /// which fails.
+    ///
+    /// # Errors
+    ///
+    /// HAL errors are wrapped in [`Error::DataCommandPin`] or [`Error::Spi`].
+    /// [`Error::ReadUnsupported`] is returned by default.
+    fn read_data<BUSY, RST>(
+        &mut self,
+        _data: &mut [u8],
+    ) -> Result<(), Error<BUSY, RST, Self::DataCommandError, Self::SpiError>>
+    where
+        BUSY: digital::Error,
+        RST: digital::Error,
+    {
+        Err(Error::ReadUnsupported)
+    }
+
+    /// Leaves the interface in its lowest power state while the panel is in deep sleep.
+    ///
+    /// # Errors
+    ///
+    /// HAL errors are wrapped in [`Error::DataCommandPin`] or [`Error::Spi`].
+    fn idle<BUSY, RST>(
+        &mut self,
+    ) -> Result<(), Error<BUSY, RST, Self::DataCommandError, Self::SpiError>>
+    where
+        BUSY: digital::Error,
+        RST: digital::Error,
+    {
+        Ok(())
+    }
+}
+
+/// 4-wire SPI with a DC pin, as used on Inkplate 6 COLOR.
+///
+/// Reads need the controller's SDA to reach the SPI peripheral, either through MISO or a
+/// half-duplex bus.  Inkplate 6 COLOR has no MISO, so reads there return whatever the bus
+/// floats to and the settings in [`crate::config::PanelConfig`] that read must stay off.
+pub struct FourWire<S, DC> {
+    pub(crate) spi: S,
+    pub(crate) dc: DC,
+}
+
+impl<S, DC> FourWire<S, DC>
+where
+    S: SpiDevice,
+    DC: OutputPin,
+{
+    pub fn new(spi: S, dc: DC) -> Self {
+        Self { spi, dc }
+    }
+
+    /// Gives back the SPI device and DC pin.
+    pub fn release(self) -> (S, DC) {
+        (self.spi, self.dc)
+    }
+}
+
+impl<S, DC> Interface for FourWire<S, DC>
+where
+    S: SpiDevice,
+    DC: OutputPin,
+{
+    type DataCommandError = DC::Error;
+    type SpiError = S::Error;
+
+    fn send_command<BUSY, RST>(
+        &mut self,
+        command: u8,
+    ) -> Result<(), Error<BUSY, RST, DC::Error, S::Error>>
+    where
+        BUSY: digital::Error,
+        RST: digital::Error,
+    {
+        self.dc.set_low().map_err(Error::DataCommandPin)?;
+        self.spi.write(&[command]).map_err(Error::Spi)
+    }
+
+    fn send_data<BUSY, RST>(
+        &mut self,
+        data: &[u8],
+    ) -> Result<(), Error<BUSY, RST, DC::Error, S::Error>>
+    where
+        BUSY: digital::Error,
+        RST: digital::Error,
+    {
+        self.dc.set_high().map_err(Error::DataCommandPin)?;
+        self.spi.write(data).map_err(Error::Spi)
+    }
+
+    fn read_data<BUSY, RST>(
+        &mut self,
+        data: &mut [u8],
+    ) -> Result<(), Error<BUSY, RST, DC::Error, S::Error>>
+    where
+        BUSY: digital::Error,
+        RST: digital::Error,
+    {
+        self.dc.set_high().map_err(Error::DataCommandPin)?;
+        self.spi.read(data).map_err(Error::Spi)
+    }
+
+    fn idle<BUSY, RST>(&mut self) -> Result<(), Error<BUSY, RST, DC::Error, S::Error>>
+    where
+        BUSY: digital::Error,
+        RST: digital::Error,
+    {
+        self.dc.set_low().map_err(Error::DataCommandPin)
+    }
+}
+
+/// 3-wire SPI using 9-bit words.  The SPI peripheral must be configured for 9-bit frames; each
+/// `u16` word carries the DC flag in bit 8.
+pub struct ThreeWire<S> {
+    spi: S,
+}
+
+impl<S> ThreeWire<S>
+where
+    S: SpiDevice<u16>,
+{
+    pub fn new(spi: S) -> Self {
+        Self { spi }
+    }
+
+    /// Gives back the SPI device.
+    pub fn release(self) -> S {
+        self.spi
+    }
+
+    fn write(&mut self, data_command: bool, bytes: &[u8]) -> Result<(), S::Error> {
+        for chunk in bytes.chunks(CHUNK_SIZE) {
+            let mut words = [0u16; CHUNK_SIZE];
+            for (word, byte) in words.iter_mut().zip(chunk) {
+                *word = (u16::from(data_command) << 8) | u16::from(*byte);
+            }
+            self.spi.write(&words[..chunk.len()])?;
+        }
+        Ok(())
+    }
+
+    /// Reads each byte as a 9-bit word, ignoring the leading dummy bit.
+    fn read(&mut self, bytes: &mut [u8]) -> Result<(), S::Error> {
+        for chunk in bytes.chunks_mut(CHUNK_SIZE) {
+            let mut words = [0u16; CHUNK_SIZE];
+            self.spi.read(&mut words[..chunk.len()])?;
+            for (byte, word) in chunk.iter_mut().zip(words) {
+                *byte = word.to_le_bytes()[0];
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S> Interface for ThreeWire<S>
+where
+    S: SpiDevice<u16>,
+{
+    type DataCommandError = Infallible;
+    type SpiError = S::Error;
+
+    fn send_command<BUSY, RST>(
+        &mut self,
+        command: u8,
+    ) -> Result<(), Error<BUSY, RST, Infallible, S::Error>>
+    where
+        BUSY: digital::Error,
+        RST: digital::Error,
+    {
+        self.write(false, &[command]).map_err(Error::Spi)
+    }
+
+    fn send_data<BUSY, RST>(
+        &mut self,
+        data: &[u8],
+    ) -> Result<(), Error<BUSY, RST, Infallible, S::Error>>
+    where
+        BUSY: digital::Error,
+        RST: digital::Error,
+    {
+        self.write(true, data).map_err(Error::Spi)
+    }
+
+    fn read_data<BUSY, RST>(
+        &mut self,
+        data: &mut [u8],
+    ) -> Result<(), Error<BUSY, RST, Infallible, S::Error>>
+    where
+        BUSY: digital::Error,
+        RST: digital::Error,
+    {
+        self.read(data).map_err(Error::Spi)
+    }
+}
+
+/// 3-wire SPI for peripherals limited to 8-bit words.  9-bit frames are packed MSB first into
+/// bytes.  Trailing padding bits of a transaction are discarded by the controller when chip
+/// select is released.
+pub struct ThreeWirePacked<S> {
+    spi: S,
+}
+
+impl<S> ThreeWirePacked<S>
+where
+    S: SpiDevice,
+{
+    pub fn new(spi: S) -> Self {
+        Self { spi }
+    }
+
+    /// Gives back the SPI device.
+    pub fn release(self) -> S {
+        self.spi
+    }
+
+    fn write(&mut self, data_command: bool, bytes: &[u8]) -> Result<(), S::Error> {
+        for chunk in bytes.chunks(CHUNK_SIZE) {
+            let mut packed = [0u8; CHUNK_SIZE / 8 * 9];
+            let len = pack(data_command, chunk, &mut packed);
+            self.spi.write(&packed[..len])?;
+        }
+        Ok(())
+    }
+}
+
+impl<S> Interface for ThreeWirePacked<S>
+where
+    S: SpiDevice,
+{
+    type DataCommandError = Infallible;
+    type SpiError = S::Error;
+
+    fn send_command<BUSY, RST>(
+        &mut self,
+        command: u8,
+    ) -> Result<(), Error<BUSY, RST, Infallible, S::Error>>
+    where
+        BUSY: digital::Error,
+        RST: digital::Error,
+    {
+        self.write(false, &[command]).map_err(Error::Spi)
+    }
+
+    fn send_data<BUSY, RST>(
+        &mut self,
+        data: &[u8],
+    ) -> Result<(), Error<BUSY, RST, Infallible, S::Error>>
+    where
+        BUSY: digital::Error,
+        RST: digital::Error,
+    {
+        self.write(true, data).map_err(Error::Spi)
+    }
+
+    /// Data is read back as plain bytes, without a DC bit.
+    fn read_data<BUSY, RST>(
+        &mut self,
+        data: &mut [u8],
+    ) -> Result<(), Error<BUSY, RST, Infallible, S::Error>>
+    where
+        BUSY: digital::Error,
+        RST: digital::Error,
+    {
+        self.spi.read(data).map_err(Error::Spi)
+    }
+}
+
+/// Packs `bytes` into 9-bit frames prefixed by `data_command`, returning the number of bytes
+/// written to `packed`.
+pub(crate) fn pack(data_command: bool, bytes: &[u8], packed: &mut [u8]) -> usize {
+    let mut accumulator: u32 = 0;
+    let mut bits = 0;
+    let mut len = 0;
+
+    for byte in bytes {
+        accumulator = (accumulator << 9) | (u32::from(data_command) << 8) | u32::from(*byte);
+        bits += 9;
+        while bits >= 8 {
+            bits -= 8;
+            packed[len] = (accumulator >> bits) as u8;
+            len += 1;
+        }
+        accumulator &= (1 << bits) - 1;
+    }
+
+    if bits > 0 {
+        packed[len] = (accumulator << (8 - bits)) as u8;
+        len += 1;
+    }
+
+    len
+}
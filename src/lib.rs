@@ -2,41 +2,112 @@
 #![doc = ::embed_doc_image::embed_image!("image-photo", "examples/image_photo.jpg")]
 #![doc = include_str!("../README.md")]
 
+// This mod MUST go first, so that the others see its macros.
+mod fmt;
+
+#[cfg(feature = "graphics")]
+pub mod adjust;
+pub mod atlas;
 pub mod color;
+pub mod compress;
+pub mod config;
+#[cfg(feature = "graphics")]
+pub mod convert;
+#[cfg(any(feature = "png", feature = "qoi"))]
+pub mod decode;
 pub mod error;
+#[cfg(feature = "inkplate6color")]
+pub mod inkplate6color;
+pub mod interface;
+pub mod packed;
+pub mod refresh;
 mod registers;
+#[cfg(feature = "serde")]
+mod serialize;
+pub mod state;
+pub mod timing;
 
 #[cfg(feature = "graphics")]
 mod graphics;
+#[cfg(feature = "graphics")]
+pub mod pattern;
+#[cfg(feature = "graphics")]
+pub mod quantize;
+#[cfg(feature = "ratatui")]
+pub mod ratatui;
 #[cfg(test)]
 mod test;
 
+use config::PanelConfig;
+use core::{marker::PhantomData, num::NonZeroUsize};
 use embedded_hal::{
     delay::DelayNs,
-    digital::{InputPin, OutputPin},
+    digital::{self, InputPin, OutputPin},
     spi::SpiDevice,
 };
+use interface::{FourWire, Interface};
+use refresh::{Phase, RefreshObserver, RefreshStats};
+use state::{Asleep, Awake, PowerState, Uninitialized};
+use timing::Timings;
 
 pub const WIDTH: usize = 600;
 pub const HEIGHT: usize = 448;
 
-pub struct Display<D, S, RST, DC, BUSY>
+/// The whole buffer, sent in a single transfer unless [`Display::set_chunk_size`] is called.
+const FRAME_CHUNK: NonZeroUsize = match NonZeroUsize::new((WIDTH * HEIGHT) / 2) {
+    Some(size) => size,
+    None => panic!("the buffer is not empty"),
+};
+
+pub(crate) type DisplayError<BUSY, RST, DI> = error::Error<
+    <BUSY as digital::ErrorType>::Error,
+    <RST as digital::ErrorType>::Error,
+    <DI as Interface>::DataCommandError,
+    <DI as Interface>::SpiError,
+>;
+
+/// The error hands back a [`Display`], so it is as large as the success value.  Boxing is not
+/// an option without `alloc`, hence `clippy::result_large_err` is allowed where it is returned.
+type Transition<D, DI, RST, BUSY, FROM, TO> = Result<
+    Display<D, DI, RST, BUSY, TO>,
+    error::TransitionError<Display<D, DI, RST, BUSY, FROM>, DisplayError<BUSY, RST, DI>>,
+>;
+
+/// Resources owned by a [`Display`], as returned by [`Display::release`], along with its
+/// settings so that [`Display::from_parts`] carries on as before.
+pub struct Parts<D, DI, RST, BUSY> {
+    pub interface: DI,
+    pub rst: RST,
+    pub busy: BUSY,
+    pub delay: D,
+    pub buffer: [u8; (WIDTH * HEIGHT) / 2],
+    pub chunk_size: NonZeroUsize,
+    pub timings: Timings,
+    pub config: PanelConfig,
+}
+
+/// Driver for the panel.  `STATE` tracks the power state of the panel (see [`state`]) so that
+/// only operations valid in that state are available.
+pub struct Display<D, DI, RST, BUSY, STATE = Uninitialized>
 where
     D: DelayNs,
-    S: SpiDevice,
+    DI: Interface,
     RST: OutputPin,
-    DC: OutputPin,
     BUSY: InputPin,
+    STATE: PowerState,
 {
-    spi: S,
+    interface: DI,
     rst: RST,
-    dc: DC,
     busy: BUSY,
     delay: D,
     buffer: [u8; (WIDTH * HEIGHT) / 2],
+    chunk_size: NonZeroUsize,
+    timings: Timings,
+    config: PanelConfig,
+    state: PhantomData<STATE>,
 }
 
-impl<D, S, RST, DC, BUSY> Display<D, S, RST, DC, BUSY>
+impl<D, S, RST, DC, BUSY> Display<D, FourWire<S, DC>, RST, BUSY, Uninitialized>
 where
     D: DelayNs,
     S: SpiDevice,
@@ -44,20 +115,38 @@ where
     DC: OutputPin,
     BUSY: InputPin,
 {
-    /// Creates a new uninitialized [`Display`].  [`Display::init`] must be called prior to
-    /// [`Display::display`].
+    /// Creates a new uninitialized [`Display`] connected through 4-wire SPI.  [`Display::init`]
+    /// must be called to obtain a [`Display`] that can be drawn to the panel.
     pub fn new(spi: S, rst: RST, dc: DC, busy: BUSY, delay: D) -> Self {
+        Self::with_interface(FourWire::new(spi, dc), rst, busy, delay)
+    }
+}
+
+impl<D, DI, RST, BUSY> Display<D, DI, RST, BUSY, Uninitialized>
+where
+    D: DelayNs,
+    DI: Interface,
+    RST: OutputPin,
+    BUSY: InputPin,
+{
+    /// Creates a new uninitialized [`Display`] connected through `interface`, e.g.
+    /// [`interface::ThreeWire`].  [`Display::init`] must be called to obtain a [`Display`] that
+    /// can be drawn to the panel.
+    pub fn with_interface(interface: DI, rst: RST, busy: BUSY, delay: D) -> Self {
         Self {
-            spi,
+            interface,
             rst,
-            dc,
             busy,
             delay,
             buffer: [0b0001_0001; (WIDTH * HEIGHT) / 2],
+            chunk_size: FRAME_CHUNK,
+            timings: Timings::default(),
+            config: PanelConfig::default(),
+            state: PhantomData,
         }
     }
 
-    /// Initializes the [`Display`].  This must be called prior to [`Display::display`].
+    /// Initializes the panel, leaving it in deep sleep.
     ///
     /// # Errors
     ///
@@ -66,12 +155,39 @@ where
     /// - [`embedded_hal::digital::Error`]
     /// - [`embedded_hal::spi::Error`]
     ///
-    /// Please consult HAL documentation for further details.
-    pub fn init(
-        &mut self,
-    ) -> Result<(), error::Error<BUSY::Error, RST::Error, DC::Error, S::Error>> {
-        self.wakeup()?;
-        self.sleep()
+    /// Please consult HAL documentation for further details.  The [`Display`] is handed back in
+    /// its previous state through [`error::TransitionError`], so it can be retried or released.
+    #[allow(clippy::result_large_err)]
+    pub fn init(mut self) -> Transition<D, DI, RST, BUSY, Uninitialized, Asleep> {
+        let initialized = self
+            .wakeup(&mut (), &mut RefreshStats::default())
+            .and_then(|()| self.deep_sleep());
+        self.transition(initialized)
+    }
+}
+
+impl<D, DI, RST, BUSY> Display<D, DI, RST, BUSY, Asleep>
+where
+    D: DelayNs,
+    DI: Interface,
+    RST: OutputPin,
+    BUSY: InputPin,
+{
+    /// Resets and configures the panel so that it is ready for [`Display::display`].
+    ///
+    /// # Errors
+    ///
+    /// Wrapped HAL errors are returned through [`error::Error`].  This includes:
+    ///
+    /// - [`embedded_hal::digital::Error`]
+    /// - [`embedded_hal::spi::Error`]
+    ///
+    /// Please consult HAL documentation for further details.  The [`Display`] is handed back in
+    /// its previous state through [`error::TransitionError`], so it can be retried or released.
+    #[allow(clippy::result_large_err)]
+    pub fn wake(mut self) -> Transition<D, DI, RST, BUSY, Asleep, Awake> {
+        let woken = self.wakeup(&mut (), &mut RefreshStats::default());
+        self.transition(woken)
     }
 
     /// 1. Wakes up the display
@@ -86,32 +202,331 @@ where
     /// - [`embedded_hal::spi::Error`]
     ///
     /// Please consult HAL documentation for further details.
-    pub fn display(
+    pub fn display(&mut self) -> Result<RefreshStats, DisplayError<BUSY, RST, DI>> {
+        self.display_observed(&mut ())
+    }
+
+    /// Like [`Display::display`] but each [`Phase`] is reported to `observer` as it happens.
+    ///
+    /// # Errors
+    ///
+    /// Wrapped HAL errors are returned through [`error::Error`].  This includes:
+    ///
+    /// - [`embedded_hal::digital::Error`]
+    /// - [`embedded_hal::spi::Error`]
+    ///
+    /// Please consult HAL documentation for further details.
+    pub fn display_observed<O>(
         &mut self,
-    ) -> Result<(), error::Error<BUSY::Error, RST::Error, DC::Error, S::Error>> {
-        self.wakeup()?;
+        observer: &mut O,
+    ) -> Result<RefreshStats, DisplayError<BUSY, RST, DI>>
+    where
+        O: RefreshObserver,
+    {
+        let mut stats = RefreshStats::default();
+        self.wakeup(observer, &mut stats)?;
+        let refreshed = self
+            .check_supply()
+            .and_then(|()| self.refresh(observer, &mut stats));
+        if let Err(e) = refreshed {
+            // best effort, the original error matters more
+            let _ = self.deep_sleep();
+            return Err(e);
+        }
 
-        self.send_command(registers::RESOLUTION_SET_REGISTER)?;
-        self.send_data(&[0x02, 0x58, 0x01, 0xc0])?;
+        observer.started(Phase::Sleep);
+        self.deep_sleep()?;
+        observer.finished(Phase::Sleep, 0);
+        Ok(stats)
+    }
 
-        self.send_command(registers::DATA_START_TRANS_REGISTER)?;
-        self.dc.set_high().map_err(error::Error::DataCommandPin)?;
+    /// Like [`Display::display`] but pixel data is produced by `fill` rather than read from the
+    /// [`Display`] buffer.  `fill` is called with the byte offset into the frame and a slice of
+    /// `chunk` to write packed pixels into, which is then sent to the panel.  `chunk` may be a
+    /// DMA-capable buffer and can be much smaller than a frame.  Each chunk is sent in its own
+    /// [`SpiDevice`] transaction, so a shared bus is given up between chunks; a `chunk` as
+    /// large as the frame holds the bus for the whole transfer.
+    ///
+    /// # Errors
+    ///
+    /// Wrapped HAL errors are returned through [`error::Error`].  This includes:
+    ///
+    /// - [`embedded_hal::digital::Error`]
+    /// - [`embedded_hal::spi::Error`]
+    ///
+    /// Please consult HAL documentation for further details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk` is empty.
+    pub fn display_with<F>(
+        &mut self,
+        chunk: &mut [u8],
+        fill: F,
+    ) -> Result<RefreshStats, DisplayError<BUSY, RST, DI>>
+    where
+        F: FnMut(usize, &mut [u8]),
+    {
+        self.display_with_observed(chunk, fill, &mut ())
+    }
 
-        self.spi.write(&self.buffer).map_err(error::Error::Spi)?;
+    /// Like [`Display::display_with`] but each [`Phase`] is reported to `observer` as it
+    /// happens.
+    ///
+    /// # Errors
+    ///
+    /// Wrapped HAL errors are returned through [`error::Error`].  This includes:
+    ///
+    /// - [`embedded_hal::digital::Error`]
+    /// - [`embedded_hal::spi::Error`]
+    ///
+    /// Please consult HAL documentation for further details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk` is empty.
+    pub fn display_with_observed<F, O>(
+        &mut self,
+        chunk: &mut [u8],
+        fill: F,
+        observer: &mut O,
+    ) -> Result<RefreshStats, DisplayError<BUSY, RST, DI>>
+    where
+        F: FnMut(usize, &mut [u8]),
+        O: RefreshObserver,
+    {
+        let mut stats = RefreshStats::default();
+        self.wakeup(observer, &mut stats)?;
+        let refreshed = self
+            .check_supply()
+            .and_then(|()| self.refresh_with(chunk, fill, observer, &mut stats));
+        if let Err(e) = refreshed {
+            // best effort, the original error matters more
+            let _ = self.deep_sleep();
+            return Err(e);
+        }
 
-        self.send_command(registers::POWER_OFF_REGISTER)?;
-        while self.busy.is_low().map_err(error::Error::BusyPin)? {}
+        observer.started(Phase::Sleep);
+        self.deep_sleep()?;
+        observer.finished(Phase::Sleep, 0);
+        Ok(stats)
+    }
 
-        self.send_command(registers::DISPLAY_REF_REGISTER)?;
-        while self.busy.is_low().map_err(error::Error::BusyPin)? {}
+    /// Reassembles a [`Display`] from [`Parts`] previously returned by [`Display::release`].
+    /// The panel must not have been woken in the meantime.
+    pub fn from_parts(parts: Parts<D, DI, RST, BUSY>) -> Self {
+        Self {
+            interface: parts.interface,
+            rst: parts.rst,
+            busy: parts.busy,
+            delay: parts.delay,
+            buffer: parts.buffer,
+            chunk_size: parts.chunk_size,
+            timings: parts.timings,
+            config: parts.config,
+            state: PhantomData,
+        }
+    }
+}
+
+impl<D, DI, RST, BUSY> Display<D, DI, RST, BUSY, Awake>
+where
+    D: DelayNs,
+    DI: Interface,
+    RST: OutputPin,
+    BUSY: InputPin,
+{
+    /// Sends contents of [`Display`] buffer to display for drawing.  The panel stays awake
+    /// afterwards so that further refreshes skip the reset and initialization sequence.
+    ///
+    /// # Errors
+    ///
+    /// Wrapped HAL errors are returned through [`error::Error`].  This includes:
+    ///
+    /// - [`embedded_hal::digital::Error`]
+    /// - [`embedded_hal::spi::Error`]
+    ///
+    /// Please consult HAL documentation for further details.
+    pub fn display(&mut self) -> Result<RefreshStats, DisplayError<BUSY, RST, DI>> {
+        self.display_observed(&mut ())
+    }
+
+    /// Like [`Display::display`] but each [`Phase`] is reported to `observer` as it happens.
+    ///
+    /// # Errors
+    ///
+    /// Wrapped HAL errors are returned through [`error::Error`].  This includes:
+    ///
+    /// - [`embedded_hal::digital::Error`]
+    /// - [`embedded_hal::spi::Error`]
+    ///
+    /// Please consult HAL documentation for further details.
+    pub fn display_observed<O>(
+        &mut self,
+        observer: &mut O,
+    ) -> Result<RefreshStats, DisplayError<BUSY, RST, DI>>
+    where
+        O: RefreshObserver,
+    {
+        let mut stats = RefreshStats::default();
+        self.check_supply()?;
+        self.refresh(observer, &mut stats)?;
+        Ok(stats)
+    }
+
+    /// Like [`Display::display`] but pixel data is produced by `fill` rather than read from the
+    /// [`Display`] buffer.  `fill` is called with the byte offset into the frame and a slice of
+    /// `chunk` to write packed pixels into, which is then sent to the panel.  `chunk` may be a
+    /// DMA-capable buffer and can be much smaller than a frame.  Each chunk is sent in its own
+    /// [`SpiDevice`] transaction, so a shared bus is given up between chunks; a `chunk` as
+    /// large as the frame holds the bus for the whole transfer.
+    ///
+    /// # Errors
+    ///
+    /// Wrapped HAL errors are returned through [`error::Error`].  This includes:
+    ///
+    /// - [`embedded_hal::digital::Error`]
+    /// - [`embedded_hal::spi::Error`]
+    ///
+    /// Please consult HAL documentation for further details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk` is empty.
+    pub fn display_with<F>(
+        &mut self,
+        chunk: &mut [u8],
+        fill: F,
+    ) -> Result<RefreshStats, DisplayError<BUSY, RST, DI>>
+    where
+        F: FnMut(usize, &mut [u8]),
+    {
+        self.display_with_observed(chunk, fill, &mut ())
+    }
+
+    /// Like [`Display::display_with`] but each [`Phase`] is reported to `observer` as it
+    /// happens.
+    ///
+    /// # Errors
+    ///
+    /// Wrapped HAL errors are returned through [`error::Error`].  This includes:
+    ///
+    /// - [`embedded_hal::digital::Error`]
+    /// - [`embedded_hal::spi::Error`]
+    ///
+    /// Please consult HAL documentation for further details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk` is empty.
+    pub fn display_with_observed<F, O>(
+        &mut self,
+        chunk: &mut [u8],
+        fill: F,
+        observer: &mut O,
+    ) -> Result<RefreshStats, DisplayError<BUSY, RST, DI>>
+    where
+        F: FnMut(usize, &mut [u8]),
+        O: RefreshObserver,
+    {
+        let mut stats = RefreshStats::default();
+        self.check_supply()?;
+        self.refresh_with(chunk, fill, observer, &mut stats)?;
+        Ok(stats)
+    }
+
+    /// Changes the frame rate used by following refreshes, e.g. to speed up a transient screen.
+    /// It is kept in the [`PanelConfig`] so it also applies after the panel is next woken.
+    ///
+    /// # Errors
+    ///
+    /// Wrapped HAL errors are returned through [`error::Error`].  This includes:
+    ///
+    /// - [`embedded_hal::digital::Error`]
+    /// - [`embedded_hal::spi::Error`]
+    ///
+    /// Please consult HAL documentation for further details.
+    pub fn set_frame_rate(
+        &mut self,
+        frame_rate: config::FrameRate,
+    ) -> Result<(), DisplayError<BUSY, RST, DI>> {
+        self.send_command(registers::PLL_CONTROL_REGISTER)?;
+        self.send_data(&[frame_rate.bits()])?;
+        self.config.frame_rate = Some(frame_rate);
+        Ok(())
+    }
+
+    /// Has the controller measure the panel's VCOM, which takes about 5 seconds.  The result
+    /// can be used as [`PanelConfig::vcom`] if the value printed on the panel is unknown.
+    ///
+    /// # Errors
+    ///
+    /// Wrapped HAL errors are returned through [`error::Error`].  This includes:
+    ///
+    /// - [`embedded_hal::digital::Error`]
+    /// - [`embedded_hal::spi::Error`]
+    ///
+    /// Please consult HAL documentation for further details.
+    pub fn measure_vcom(&mut self) -> Result<config::Vcom, DisplayError<BUSY, RST, DI>> {
+        self.send_command(registers::POWER_ON_REGISTER)?;
+        self.wait_while_busy(false)?;
+
+        // 5 s measurement, enabled
+        self.send_command(registers::AUTO_MEASURE_VCOM_REGISTER)?;
+        self.send_data(&[0x11])?;
+        self.wait_while_busy(false)?;
+
+        self.send_command(registers::VCOM_VALUE_REGISTER)?;
+        let mut value = [0];
+        self.interface.read_data(&mut value)?;
 
         self.send_command(registers::POWER_OFF_REGISTER)?;
-        while self.busy.is_high().map_err(error::Error::BusyPin)? {}
+        self.wait_while_busy(true)?;
+        self.delay.delay_us(self.timings.power_off_us);
+        Ok(config::Vcom::from_bits(value[0]))
+    }
+
+    /// Whether the supply is below [`config::PanelConfig::low_power_threshold`], as read from
+    /// the controller's low-voltage detector.
+    ///
+    /// # Errors
+    ///
+    /// Wrapped HAL errors are returned through [`error::Error`].  This includes:
+    ///
+    /// - [`embedded_hal::digital::Error`]
+    /// - [`embedded_hal::spi::Error`]
+    ///
+    /// Please consult HAL documentation for further details.
+    pub fn low_power_detected(&mut self) -> Result<bool, DisplayError<BUSY, RST, DI>> {
+        self.read_low_power()
+    }
 
-        self.delay.delay_ms(200u32);
-        self.sleep()
+    /// Puts the panel into deep sleep.
+    ///
+    /// # Errors
+    ///
+    /// Wrapped HAL errors are returned through [`error::Error`].  This includes:
+    ///
+    /// - [`embedded_hal::digital::Error`]
+    /// - [`embedded_hal::spi::Error`]
+    ///
+    /// Please consult HAL documentation for further details.  The [`Display`] is handed back in
+    /// its previous state through [`error::TransitionError`], so it can be retried or released.
+    #[allow(clippy::result_large_err)]
+    pub fn sleep(mut self) -> Transition<D, DI, RST, BUSY, Awake, Asleep> {
+        let slept = self.deep_sleep();
+        self.transition(slept)
     }
+}
 
+impl<D, DI, RST, BUSY, STATE> Display<D, DI, RST, BUSY, STATE>
+where
+    D: DelayNs,
+    DI: Interface,
+    RST: OutputPin,
+    BUSY: InputPin,
+    STATE: PowerState,
+{
     /// Sets a pixel in the buffer at (`x`, `y`) to `color`.
     ///
     /// # Errors
@@ -123,60 +538,269 @@ where
         x: usize,
         y: usize,
         color: color::Color,
-    ) -> Result<(), error::Error<BUSY::Error, RST::Error, DC::Error, S::Error>> {
-        let color: u8 = color.into();
-
-        if let Some(byte) = self.buffer.get_mut((x >> 1) + y * WIDTH / 2) {
-            if x % 2 == 0 {
-                *byte = (*byte & 0x0f) | (color << 4);
-            } else {
-                *byte = (*byte & 0xf0) | color;
-            }
+    ) -> Result<(), DisplayError<BUSY, RST, DI>> {
+        if self.canvas_mut().set_pixel(x, y, color) {
             Ok(())
         } else {
             Err(error::Error::PixelOutOfBounds)
         }
     }
 
-    fn reset_panel(
+    /// Splits transfers of the [`Display`] buffer into chunks of at most `chunk_size` bytes, for
+    /// HALs that cap DMA descriptors or transfer lengths.  Each chunk is a separate
+    /// [`SpiDevice`] transaction, so this is also how a shared bus is given up between chunks,
+    /// letting other devices on it be served during a transfer.  By default the whole buffer is
+    /// sent in one transaction, holding the bus until it is done.
+    pub fn set_chunk_size(&mut self, chunk_size: NonZeroUsize) {
+        self.chunk_size = chunk_size;
+    }
+
+    /// Replaces the delays used when driving the panel.  See [`Timings::default`] for the values
+    /// used otherwise.
+    pub fn set_timings(&mut self, timings: Timings) {
+        self.timings = timings;
+    }
+
+    /// Replaces the controller settings sent when the panel is woken.  An awake panel picks
+    /// them up on its next wake.
+    pub fn set_config(&mut self, config: PanelConfig) {
+        self.config = config;
+    }
+
+    /// Gives back the resources owned by the [`Display`], including its buffer and settings.
+    /// An [`Asleep`] panel is in deep sleep so pins can safely be reconfigured, e.g. into
+    /// low-leakage states.  An [`Awake`] panel is left awake, so put it to [`Display::sleep`]
+    /// first where possible.
+    pub fn release(self) -> Parts<D, DI, RST, BUSY> {
+        Parts {
+            interface: self.interface,
+            rst: self.rst,
+            busy: self.busy,
+            delay: self.delay,
+            buffer: self.buffer,
+            chunk_size: self.chunk_size,
+            timings: self.timings,
+            config: self.config,
+        }
+    }
+
+    fn into_state<T: PowerState>(self) -> Display<D, DI, RST, BUSY, T> {
+        Display {
+            interface: self.interface,
+            rst: self.rst,
+            busy: self.busy,
+            delay: self.delay,
+            buffer: self.buffer,
+            chunk_size: self.chunk_size,
+            timings: self.timings,
+            config: self.config,
+            state: PhantomData,
+        }
+    }
+
+    /// Moves to state `T` if `result` is `Ok`, or hands `self` back with the error.
+    #[allow(clippy::result_large_err)]
+    fn transition<T: PowerState>(
+        self,
+        result: Result<(), DisplayError<BUSY, RST, DI>>,
+    ) -> Transition<D, DI, RST, BUSY, STATE, T> {
+        match result {
+            Ok(()) => Ok(self.into_state()),
+            Err(error) => Err(error::TransitionError {
+                display: self,
+                error,
+            }),
+        }
+    }
+
+    fn refresh<O>(
+        &mut self,
+        observer: &mut O,
+        stats: &mut RefreshStats,
+    ) -> Result<(), DisplayError<BUSY, RST, DI>>
+    where
+        O: RefreshObserver,
+    {
+        observer.started(Phase::Transfer);
+        let mut retries = 0;
+        loop {
+            self.start_transfer()?;
+            for chunk in self.buffer.chunks(self.chunk_size.get()) {
+                self.interface.send_data(chunk)?;
+            }
+            if self.transfer_complete(&mut retries)? {
+                break;
+            }
+        }
+        observer.finished(Phase::Transfer, 0);
+        self.finish_refresh(observer, stats)
+    }
+
+    fn refresh_with<F, O>(
+        &mut self,
+        chunk: &mut [u8],
+        mut fill: F,
+        observer: &mut O,
+        stats: &mut RefreshStats,
+    ) -> Result<(), DisplayError<BUSY, RST, DI>>
+    where
+        F: FnMut(usize, &mut [u8]),
+        O: RefreshObserver,
+    {
+        assert!(!chunk.is_empty());
+
+        observer.started(Phase::Transfer);
+        let mut retries = 0;
+        loop {
+            self.start_transfer()?;
+            let mut offset = 0;
+            while offset < self.buffer.len() {
+                let len = chunk.len().min(self.buffer.len() - offset);
+                fill(offset, &mut chunk[..len]);
+                self.interface.send_data(&chunk[..len])?;
+                offset += len;
+            }
+            if self.transfer_complete(&mut retries)? {
+                break;
+            }
+        }
+        observer.finished(Phase::Transfer, 0);
+        self.finish_refresh(observer, stats)
+    }
+
+    fn start_transfer(&mut self) -> Result<(), DisplayError<BUSY, RST, DI>> {
+        self.send_command(registers::RESOLUTION_SET_REGISTER)?;
+        self.send_data(&[0x02, 0x58, 0x01, 0xc0])?;
+
+        self.send_command(registers::DATA_START_TRANS_REGISTER)
+    }
+
+    /// Whether the frame just sent should be refreshed, or `false` if it should be sent again.
+    /// `retries` counts the frames sent again so far.
+    fn transfer_complete(&mut self, retries: &mut u8) -> Result<bool, DisplayError<BUSY, RST, DI>> {
+        if !self.config.verify_transfer {
+            return Ok(true);
+        }
+
+        self.send_command(registers::DATA_STOP_REGISTER)?;
+        let mut flag = [0];
+        self.interface.read_data(&mut flag)?;
+        if flag[0] & 0x80 != 0 {
+            Ok(true)
+        } else if *retries < self.config.transfer_retries {
+            *retries += 1;
+            debug!("frame incomplete, retry {}", *retries);
+            Ok(false)
+        } else {
+            Err(error::Error::TransferIncomplete)
+        }
+    }
+
+    fn finish_refresh<O>(
         &mut self,
-    ) -> Result<(), error::Error<BUSY::Error, RST::Error, DC::Error, S::Error>> {
+        observer: &mut O,
+        stats: &mut RefreshStats,
+    ) -> Result<(), DisplayError<BUSY, RST, DI>>
+    where
+        O: RefreshObserver,
+    {
+        observer.started(Phase::PowerOn);
+        self.send_command(registers::POWER_ON_REGISTER)?;
+        stats.power_on_busy_us = self.wait_while_busy(false)?;
+        observer.finished(Phase::PowerOn, stats.power_on_busy_us);
+
+        observer.started(Phase::Refresh);
+        self.send_command(registers::DISPLAY_REF_REGISTER)?;
+        stats.refresh_busy_us = self.wait_while_busy(false)?;
+        observer.finished(Phase::Refresh, stats.refresh_busy_us);
+
+        observer.started(Phase::PowerOff);
+        // the Inkplate sequence this follows sends PON again here rather than POF
+        self.send_command(registers::POWER_ON_REGISTER)?;
+        stats.power_off_busy_us = self.wait_while_busy(true)?;
+        self.delay.delay_us(self.timings.power_off_us);
+        observer.finished(Phase::PowerOff, stats.power_off_busy_us);
+        Ok(())
+    }
+
+    fn reset_panel(&mut self) -> Result<(), DisplayError<BUSY, RST, DI>> {
         self.rst.set_low().map_err(error::Error::ResetPin)?;
-        self.delay.delay_ms(1u32);
+        self.delay.delay_us(self.timings.reset_pulse_us);
         self.rst.set_high().map_err(error::Error::ResetPin)?;
-        self.delay.delay_ms(200u32);
+        self.delay.delay_us(self.timings.reset_wait_us);
         Ok(())
     }
 
-    fn send_command(
-        &mut self,
-        command: u8,
-    ) -> Result<(), error::Error<BUSY::Error, RST::Error, DC::Error, S::Error>> {
-        self.dc.set_low().map_err(error::Error::DataCommandPin)?;
-        self.spi.write(&[command]).map_err(error::Error::Spi)
+    fn send_command(&mut self, command: u8) -> Result<(), DisplayError<BUSY, RST, DI>> {
+        trace!("command {}", registers::name(command));
+        self.interface.send_command(command)
     }
 
-    fn send_data(
-        &mut self,
-        data: &[u8],
-    ) -> Result<(), error::Error<BUSY::Error, RST::Error, DC::Error, S::Error>> {
-        self.dc.set_high().map_err(error::Error::DataCommandPin)?;
-        self.spi.write(data).map_err(error::Error::Spi)
+    fn send_data(&mut self, data: &[u8]) -> Result<(), DisplayError<BUSY, RST, DI>> {
+        self.interface.send_data(data)
+    }
+
+    fn read_low_power(&mut self) -> Result<bool, DisplayError<BUSY, RST, DI>> {
+        self.send_command(registers::LOW_POWER_DETECT_REGISTER)?;
+        let mut flag = [0];
+        self.interface.read_data(&mut flag)?;
+        // the flag reads 0 while the supply is low
+        Ok(flag[0] & 0x01 == 0)
+    }
+
+    /// Fails with [`error::Error::LowPower`] if refreshing from a low supply is refused.
+    fn check_supply(&mut self) -> Result<(), DisplayError<BUSY, RST, DI>> {
+        if self.config.refuse_low_power && self.read_low_power()? {
+            debug!("supply too low to refresh");
+            return Err(error::Error::LowPower);
+        }
+        Ok(())
     }
 
-    fn sleep(&mut self) -> Result<(), error::Error<BUSY::Error, RST::Error, DC::Error, S::Error>> {
-        self.delay.delay_ms(10u32);
+    fn deep_sleep(&mut self) -> Result<(), DisplayError<BUSY, RST, DI>> {
+        self.delay.delay_us(self.timings.sleep_setup_us);
         self.send_command(registers::DEEP_SLEEP_REGISTER)?;
         self.send_data(&[0xA5])?;
-        self.delay.delay_ms(100u32);
+        self.delay.delay_us(self.timings.sleep_hold_us);
         self.rst.set_low().map_err(error::Error::ResetPin)?;
-        self.dc.set_low().map_err(error::Error::DataCommandPin)
+        self.interface.idle()?;
+        debug!("panel in deep sleep");
+        Ok(())
+    }
+
+    /// Polls BUSY until it leaves `level`, returning the time waited in microseconds.
+    fn wait_while_busy(&mut self, level: bool) -> Result<u32, DisplayError<BUSY, RST, DI>> {
+        let mut elapsed_us: u32 = 0;
+        while self.busy.is_high().map_err(error::Error::BusyPin)? == level {
+            if self
+                .timings
+                .busy_timeout_us
+                .is_some_and(|timeout_us| elapsed_us >= timeout_us)
+            {
+                return Err(error::Error::BusyTimeout);
+            }
+            self.delay.delay_us(self.timings.busy_poll_us);
+            elapsed_us = elapsed_us.saturating_add(self.timings.busy_poll_us);
+        }
+        debug!("busy for {} us", elapsed_us);
+        Ok(elapsed_us)
     }
 
-    fn wakeup(&mut self) -> Result<(), error::Error<BUSY::Error, RST::Error, DC::Error, S::Error>> {
+    fn wakeup<O>(
+        &mut self,
+        observer: &mut O,
+        stats: &mut RefreshStats,
+    ) -> Result<(), DisplayError<BUSY, RST, DI>>
+    where
+        O: RefreshObserver,
+    {
+        debug!("waking panel");
+        observer.started(Phase::Reset);
         self.reset_panel()?;
+        stats.reset_busy_us = self.wait_while_busy(false)?;
+        observer.finished(Phase::Reset, stats.reset_busy_us);
 
-        while self.busy.is_low().map_err(error::Error::BusyPin)? {}
+        observer.started(Phase::Init);
 
         self.send_command(registers::PANEL_SET_REGISTER)?;
         self.send_data(&[0xEF, 0x08])?;
@@ -196,17 +820,34 @@ where
         self.send_command(registers::VCOM_DATA_INTERVAL_REGISTER)?;
         self.send_data(&[0x37])?;
 
-        self.send_command(0x60)?;
+        self.send_command(registers::TCON_SET_REGISTER)?;
         self.send_data(&[0x20])?;
 
         self.send_command(registers::RESOLUTION_SET_REGISTER)?;
         self.send_data(&[0x02, 0x58, 0x01, 0xC0])?;
 
-        self.send_command(0xE3)?;
+        self.send_command(registers::POWER_SAVING_REGISTER)?;
         self.send_data(&[0xAA])?;
 
-        self.delay.delay_ms(100u32);
+        if let Some(frame_rate) = self.config.frame_rate {
+            self.send_command(registers::PLL_CONTROL_REGISTER)?;
+            self.send_data(&[frame_rate.bits()])?;
+        }
+
+        if let Some(vcom) = self.config.vcom {
+            self.send_command(registers::VCOM_DC_SETTING_REGISTER)?;
+            self.send_data(&[vcom.bits()])?;
+        }
+
+        if let Some(threshold) = self.config.low_power_threshold {
+            self.send_command(registers::LOW_VOLTAGE_SELECT_REGISTER)?;
+            self.send_data(&[threshold.bits()])?;
+        }
+
+        self.delay.delay_us(self.timings.vcom_setup_us);
         self.send_command(registers::VCOM_DATA_INTERVAL_REGISTER)?;
-        self.send_data(&[0x37])
+        self.send_data(&[0x37])?;
+        observer.finished(Phase::Init, 0);
+        Ok(())
     }
 }
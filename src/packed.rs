@@ -0,0 +1,419 @@
+//! Images stored in the panel's native layout of two pixels per byte.
+//!
+//! The left pixel of each pair is in the high nibble, as in the [`Display`] buffer.  Rows start
+//! on a byte boundary, so images with an odd width have an unused low nibble at the end of each
+//! row.  [`PackedImage`] borrows such data read-only, while [`Canvas`] can also be drawn to and
+//! holds the packing code used by [`Display`] itself.
+
+use crate::{color::Color, interface::Interface, state::PowerState, Display, WIDTH};
+use core::cmp::Ordering;
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{InputPin, OutputPin},
+};
+
+/// Borrowed image data in the panel's nibble layout, e.g. a splash screen converted ahead of
+/// time and included with [`include_bytes!`].
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+pub struct PackedImage<'a> {
+    data: &'a [u8],
+    width: usize,
+    height: usize,
+}
+
+impl<'a> PackedImage<'a> {
+    /// Creates an image `width` pixels wide.  The height is the number of whole rows in `data`.
+    pub const fn new(data: &'a [u8], width: usize) -> Self {
+        let height = if width == 0 {
+            0
+        } else {
+            data.len() / width.div_ceil(2)
+        };
+        Self {
+            data,
+            width,
+            height,
+        }
+    }
+
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Bytes per row.
+    pub const fn stride(&self) -> usize {
+        self.width.div_ceil(2)
+    }
+
+    /// Packed bytes of all rows.
+    pub fn data(&self) -> &'a [u8] {
+        &self.data[..self.stride() * self.height]
+    }
+
+    /// Packed bytes of row `y`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `y` is not less than the height.
+    pub fn row(&self, y: usize) -> &'a [u8] {
+        assert!(y < self.height);
+        let stride = self.stride();
+        &self.data[y * stride..(y + 1) * stride]
+    }
+
+    /// The pixel at (`x`, `y`), or `None` if it lies outside the image or holds an invalid
+    /// color.
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Color::try_from(nibble(self.row(y), x)).ok()
+    }
+}
+
+/// Off-screen image in the panel's nibble layout that can be drawn to, e.g. to compose layers
+/// before compositing them onto a [`Display`] with [`Display::draw_packed`] and
+/// [`Canvas::as_image`].  `B` is the storage, such as `[u8; N]` or `&mut [u8]`.
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+pub struct Canvas<B> {
+    buffer: B,
+    width: usize,
+    height: usize,
+}
+
+impl<B> Canvas<B>
+where
+    B: AsRef<[u8]>,
+{
+    /// Creates a canvas `width` pixels wide over `buffer`.  The height is the number of whole
+    /// rows in `buffer`.
+    pub fn new(buffer: B, width: usize) -> Self {
+        let height = PackedImage::new(buffer.as_ref(), width).height();
+        Self {
+            buffer,
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Gives back the storage.
+    pub fn into_inner(self) -> B {
+        self.buffer
+    }
+
+    /// Views the canvas as a [`PackedImage`], e.g. to draw it onto a [`Display`].
+    pub fn as_image(&self) -> PackedImage<'_> {
+        PackedImage::new(
+            &self.buffer.as_ref()[..self.width.div_ceil(2) * self.height],
+            self.width,
+        )
+    }
+
+    /// The pixel at (`x`, `y`), or `None` if it lies outside the canvas or holds an invalid
+    /// color.
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Color> {
+        self.as_image().pixel(x, y)
+    }
+
+    /// Copies the area of the canvas `width` by `height` pixels at (`x`, `y`) into `buffer`.
+    /// The area is clipped to the canvas.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is smaller than the clipped area, which takes `width.div_ceil(2) *
+    /// height` bytes at most.
+    pub fn save_area<'b>(
+        &self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        buffer: &'b mut [u8],
+    ) -> PackedImage<'b> {
+        let image = self.as_image();
+        let width = width.min(self.width.saturating_sub(x));
+        let height = height.min(self.height.saturating_sub(y));
+        let stride = width.div_ceil(2);
+        let buffer = &mut buffer[..stride * height];
+
+        for (row_y, row) in buffer.chunks_exact_mut(stride.max(1)).enumerate() {
+            copy_nibbles(image.row(y + row_y), x, row, 0, width);
+        }
+
+        PackedImage::new(buffer, width)
+    }
+}
+
+impl<B> Canvas<B>
+where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /// Sets the pixel at (`x`, `y`) to `color`, returning `false` if it lies outside the canvas.
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        set_nibble(self.row_mut(y), x, color.into());
+        true
+    }
+
+    /// Sets every pixel to `color`.
+    pub fn clear(&mut self, color: Color) {
+        let color: u8 = color.into();
+        self.buffer.as_mut().fill((color << 4) | color);
+    }
+
+    /// Copies `image` onto the canvas with its top left corner at (`x`, `y`), clipping anything
+    /// outside the canvas.  When `x` is even, whole rows are copied as byte slices rather than
+    /// pixel by pixel.
+    pub fn draw_packed(&mut self, image: &PackedImage<'_>, x: usize, y: usize) {
+        let columns = image.width().min(self.width.saturating_sub(x));
+        let rows = image.height().min(self.height.saturating_sub(y));
+
+        for row_y in 0..rows {
+            copy_nibbles(image.row(row_y), 0, self.row_mut(y + row_y), x, columns);
+        }
+    }
+
+    /// Like [`Canvas::draw_packed`] but pixels of `image` that are `key` are left out, so that
+    /// whatever is underneath shows through.
+    pub fn draw_packed_keyed(&mut self, image: &PackedImage<'_>, x: usize, y: usize, key: Color) {
+        self.draw_columns_keyed(image, 0, image.width(), x, y, key.into());
+    }
+
+    /// Draws `width` columns of `image` from `source_x` onward, leaving out pixels whose nibble
+    /// is `key`.
+    pub(crate) fn draw_columns_keyed(
+        &mut self,
+        image: &PackedImage<'_>,
+        source_x: usize,
+        width: usize,
+        x: usize,
+        y: usize,
+        key: u8,
+    ) {
+        let columns = width
+            .min(image.width().saturating_sub(source_x))
+            .min(self.width.saturating_sub(x));
+        let rows = image.height().min(self.height.saturating_sub(y));
+
+        for row_y in 0..rows {
+            let source = image.row(row_y);
+            let row = self.row_mut(y + row_y);
+            for row_x in 0..columns {
+                let value = nibble(source, source_x + row_x);
+                if value != key {
+                    set_nibble(row, x + row_x, value);
+                }
+            }
+        }
+    }
+
+    /// Copies the area `width` by `height` pixels at (`source_x`, `source_y`) to (`x`, `y`)
+    /// within the canvas.  The areas may overlap.  Both areas are clipped to the canvas.
+    pub fn copy_area(
+        &mut self,
+        source_x: usize,
+        source_y: usize,
+        width: usize,
+        height: usize,
+        x: usize,
+        y: usize,
+    ) {
+        let width = width
+            .min(self.width.saturating_sub(source_x))
+            .min(self.width.saturating_sub(x));
+        let height = height
+            .min(self.height.saturating_sub(source_y))
+            .min(self.height.saturating_sub(y));
+        let stride = self.width.div_ceil(2);
+
+        let mut copy_row = |row_y: usize| {
+            let source = (source_y + row_y) * stride;
+            let target = (y + row_y) * stride;
+            let buffer = self.buffer.as_mut();
+            match source.cmp(&target) {
+                Ordering::Equal => {
+                    copy_nibbles_within(&mut buffer[target..target + stride], source_x, x, width);
+                }
+                Ordering::Less => {
+                    let (before, after) = buffer.split_at_mut(target);
+                    copy_nibbles(&before[source..source + stride], source_x, after, x, width);
+                }
+                Ordering::Greater => {
+                    let (before, after) = buffer.split_at_mut(source);
+                    copy_nibbles(&after[..stride], source_x, &mut before[target..], x, width);
+                }
+            }
+        };
+        // copy rows in an order that reads each source row before it is overwritten
+        if y <= source_y {
+            (0..height).for_each(&mut copy_row);
+        } else {
+            (0..height).rev().for_each(&mut copy_row);
+        }
+    }
+
+    fn row_mut(&mut self, y: usize) -> &mut [u8] {
+        let stride = self.width.div_ceil(2);
+        &mut self.buffer.as_mut()[y * stride..(y + 1) * stride]
+    }
+}
+
+impl<D, DI, RST, BUSY, STATE> Display<D, DI, RST, BUSY, STATE>
+where
+    D: DelayNs,
+    DI: Interface,
+    RST: OutputPin,
+    BUSY: InputPin,
+    STATE: PowerState,
+{
+    /// Views the [`Display`] buffer as a [`PackedImage`], e.g. to save or send the frame.
+    pub fn as_image(&self) -> PackedImage<'_> {
+        PackedImage::new(&self.buffer, WIDTH)
+    }
+
+    /// Copies `image` into the [`Display`] buffer with its top left corner at (`x`, `y`),
+    /// clipping anything outside the panel.  When `x` is even, whole rows are copied as byte
+    /// slices rather than pixel by pixel.
+    pub fn draw_packed(&mut self, image: &PackedImage<'_>, x: usize, y: usize) {
+        self.canvas_mut().draw_packed(image, x, y);
+    }
+
+    /// Like [`Display::draw_packed`] but pixels of `image` that are `key` are left out, so that
+    /// whatever is underneath shows through.
+    pub fn draw_packed_keyed(&mut self, image: &PackedImage<'_>, x: usize, y: usize, key: Color) {
+        self.canvas_mut().draw_packed_keyed(image, x, y, key);
+    }
+
+    /// Copies the area of the [`Display`] buffer `width` by `height` pixels at (`x`, `y`) into
+    /// `buffer`, e.g. to save what is underneath an overlay.  The area is clipped to the panel.
+    /// The returned image can be put back with [`Display::draw_packed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is smaller than the clipped area, which takes `width.div_ceil(2) *
+    /// height` bytes at most.
+    pub fn save_area<'b>(
+        &self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        buffer: &'b mut [u8],
+    ) -> PackedImage<'b> {
+        Canvas::new(&self.buffer[..], WIDTH).save_area(x, y, width, height, buffer)
+    }
+
+    /// Copies the area `width` by `height` pixels at (`source_x`, `source_y`) to
+    /// (`x`, `y`) within the [`Display`] buffer.  The areas may overlap.  Both areas are clipped
+    /// to the panel.
+    pub fn copy_area(
+        &mut self,
+        source_x: usize,
+        source_y: usize,
+        width: usize,
+        height: usize,
+        x: usize,
+        y: usize,
+    ) {
+        self.canvas_mut()
+            .copy_area(source_x, source_y, width, height, x, y);
+    }
+
+    pub(crate) fn canvas_mut(&mut self) -> Canvas<&mut [u8]> {
+        Canvas::new(&mut self.buffer[..], WIDTH)
+    }
+}
+
+fn nibble(bytes: &[u8], x: usize) -> u8 {
+    if x.is_multiple_of(2) {
+        bytes[x / 2] >> 4
+    } else {
+        bytes[x / 2] & 0x0f
+    }
+}
+
+fn set_nibble(bytes: &mut [u8], x: usize, value: u8) {
+    let byte = &mut bytes[x / 2];
+    if x.is_multiple_of(2) {
+        *byte = (*byte & 0x0f) | (value << 4);
+    } else {
+        *byte = (*byte & 0xf0) | value;
+    }
+}
+
+/// Copies `len` pixels starting at pixel `source_x` of `source` to pixel `x` of `target`.
+/// Bytes are copied whole when both start on the same half of a byte.
+fn copy_nibbles(source: &[u8], source_x: usize, target: &mut [u8], x: usize, len: usize) {
+    if len == 0 {
+        return;
+    }
+
+    if source_x % 2 != x % 2 {
+        for i in 0..len {
+            set_nibble(target, x + i, nibble(source, source_x + i));
+        }
+        return;
+    }
+
+    let mut i = 0;
+    if !x.is_multiple_of(2) {
+        set_nibble(target, x, nibble(source, source_x));
+        i = 1;
+    }
+    let bytes = (len - i) / 2;
+    let start = (x + i) / 2;
+    let source_start = (source_x + i) / 2;
+    target[start..start + bytes].copy_from_slice(&source[source_start..source_start + bytes]);
+    i += bytes * 2;
+    if i < len {
+        set_nibble(target, x + i, nibble(source, source_x + i));
+    }
+}
+
+/// Like [`copy_nibbles`] but within one row, where the two runs of pixels may overlap.
+fn copy_nibbles_within(row: &mut [u8], source_x: usize, x: usize, len: usize) {
+    if len == 0 || source_x == x {
+        return;
+    }
+
+    if source_x % 2 != x % 2 {
+        // copy in the direction that reads each pixel before it is overwritten
+        if x < source_x {
+            for i in 0..len {
+                set_nibble(row, x + i, nibble(row, source_x + i));
+            }
+        } else {
+            for i in (0..len).rev() {
+                set_nibble(row, x + i, nibble(row, source_x + i));
+            }
+        }
+        return;
+    }
+
+    let first = nibble(row, source_x);
+    let last = nibble(row, source_x + len - 1);
+    let i = x % 2;
+    let bytes = (len - i) / 2;
+    let source_start = (source_x + i) / 2;
+    row.copy_within(source_start..source_start + bytes, (x + i) / 2);
+    if i == 1 {
+        set_nibble(row, x, first);
+    }
+    if i + bytes * 2 < len {
+        set_nibble(row, x + len - 1, last);
+    }
+}
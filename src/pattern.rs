@@ -0,0 +1,123 @@
+//! "Virtual" colors made of a repeating tile of inks, for UI fills beyond the 7 inks without
+//! dithering.
+//!
+//! Tiles are anchored to the co-ordinates of the target rather than to each shape, so adjoining
+//! fills and strokes of the same [`PatternColor`] line up.
+
+use crate::color::Color;
+use embedded_graphics_core::{
+    draw_target::DrawTarget,
+    pixelcolor::PixelColor,
+    prelude::{Dimensions, Point},
+    primitives::{PointsIter, Rectangle},
+    Pixel,
+};
+
+/// A 2x2 tile of inks.  Drawn through [`Patterned`], each pixel takes the ink at its position
+/// in the tile.
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PatternColor {
+    tile: [[Color; 2]; 2],
+}
+
+impl PatternColor {
+    pub const LIGHT_GRAY: Self =
+        Self::new([[Color::BLACK, Color::WHITE], [Color::WHITE, Color::WHITE]]);
+    pub const GRAY: Self = Self::checker(Color::BLACK, Color::WHITE);
+    pub const DARK_GRAY: Self =
+        Self::new([[Color::BLACK, Color::WHITE], [Color::BLACK, Color::BLACK]]);
+    pub const PINK: Self = Self::checker(Color::RED, Color::WHITE);
+    pub const BROWN: Self = Self::checker(Color::RED, Color::BLACK);
+    pub const PURPLE: Self = Self::checker(Color::RED, Color::BLUE);
+    pub const LIGHT_BLUE: Self = Self::checker(Color::BLUE, Color::WHITE);
+    pub const NAVY: Self = Self::checker(Color::BLUE, Color::BLACK);
+    pub const LIME: Self = Self::checker(Color::GREEN, Color::YELLOW);
+    pub const DARK_GREEN: Self = Self::checker(Color::GREEN, Color::BLACK);
+
+    /// A tile given as rows of inks.
+    pub const fn new(tile: [[Color; 2]; 2]) -> Self {
+        Self { tile }
+    }
+
+    /// A single ink.
+    pub const fn solid(color: Color) -> Self {
+        Self::new([[color, color], [color, color]])
+    }
+
+    /// A checkerboard of `a` and `b`, with `a` at even co-ordinates.
+    pub const fn checker(a: Color, b: Color) -> Self {
+        Self::new([[a, b], [b, a]])
+    }
+
+    /// The ink for a pixel at `point`.
+    pub fn resolve(&self, point: Point) -> Color {
+        // rem_euclid keeps the tile aligned across negative co-ordinates
+        #[allow(clippy::cast_sign_loss)]
+        let (x, y) = (
+            point.x.rem_euclid(2) as usize,
+            point.y.rem_euclid(2) as usize,
+        );
+        self.tile[y][x]
+    }
+}
+
+impl From<Color> for PatternColor {
+    fn from(color: Color) -> Self {
+        Self::solid(color)
+    }
+}
+
+impl PixelColor for PatternColor {
+    type Raw = ();
+}
+
+/// Draws [`PatternColor`] onto a target of [`Color`], such as a [`crate::Display`] or
+/// [`crate::packed::Canvas`].
+pub struct Patterned<'a, T> {
+    target: &'a mut T,
+}
+
+impl<'a, T> Patterned<'a, T>
+where
+    T: DrawTarget<Color = Color>,
+{
+    pub fn new(target: &'a mut T) -> Self {
+        Self { target }
+    }
+}
+
+impl<T> Dimensions for Patterned<'_, T>
+where
+    T: DrawTarget<Color = Color>,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<T> DrawTarget for Patterned<'_, T>
+where
+    T: DrawTarget<Color = Color>,
+{
+    type Color = PatternColor;
+    type Error = T::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.target.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(point, color)| Pixel(point, color.resolve(point))),
+        )
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&self.bounding_box());
+        self.target
+            .fill_contiguous(&area, area.points().map(|point| color.resolve(point)))
+    }
+}
@@ -0,0 +1,82 @@
+//! Reduction of [`Rgb888`] pixels to the panel's [`Color`] inks.
+
+use crate::{color::Color, WIDTH};
+use embedded_graphics_core::pixelcolor::{Rgb888, RgbColor};
+
+/// Maps [`Rgb888`] pixels to [`Color`].  Pixels are visited in row-major order, so
+/// implementations may carry state such as diffused error from one pixel to the next.
+pub trait Quantizer {
+    fn quantize(&mut self, x: usize, y: usize, color: Rgb888) -> Color;
+}
+
+/// Maps each pixel to the closest [`Color`] through [`From<Rgb888>`].
+#[derive(Default, Debug, Eq, Hash, PartialEq, Copy, Clone)]
+pub struct Nearest;
+
+impl Quantizer for Nearest {
+    fn quantize(&mut self, _x: usize, _y: usize, color: Rgb888) -> Color {
+        color.into()
+    }
+}
+
+/// Floyd-Steinberg error diffusion over rows up to [`WIDTH`] pixels wide.
+pub struct FloydSteinberg {
+    y: usize,
+    current: [[i16; 3]; WIDTH + 2],
+    next: [[i16; 3]; WIDTH + 2],
+}
+
+impl FloydSteinberg {
+    pub fn new() -> Self {
+        Self {
+            y: 0,
+            current: [[0; 3]; WIDTH + 2],
+            next: [[0; 3]; WIDTH + 2],
+        }
+    }
+}
+
+impl Default for FloydSteinberg {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Quantizer for FloydSteinberg {
+    fn quantize(&mut self, x: usize, y: usize, color: Rgb888) -> Color {
+        if y != self.y {
+            self.y = y;
+            self.current = self.next;
+            self.next = [[0; 3]; WIDTH + 2];
+        }
+
+        // errors are offset by one so that x - 1 never underflows
+        let Some(error) = self.current.get(x + 1).copied() else {
+            return color.into();
+        };
+        let wanted = [
+            (i16::from(color.r()) + error[0] / 16).clamp(0, 255),
+            (i16::from(color.g()) + error[1] / 16).clamp(0, 255),
+            (i16::from(color.b()) + error[2] / 16).clamp(0, 255),
+        ];
+        #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+        let quantized: Color =
+            Rgb888::new(wanted[0] as u8, wanted[1] as u8, wanted[2] as u8).into();
+        let actual: Rgb888 = quantized.into();
+        let actual = [actual.r(), actual.g(), actual.b()];
+
+        for channel in 0..3 {
+            let residual = wanted[channel] - i16::from(actual[channel]);
+            if let Some(right) = self.current.get_mut(x + 2) {
+                right[channel] += residual * 7;
+            }
+            self.next[x][channel] += residual * 3;
+            self.next[x + 1][channel] += residual * 5;
+            if let Some(below_right) = self.next.get_mut(x + 2) {
+                below_right[channel] += residual;
+            }
+        }
+
+        quantized
+    }
+}
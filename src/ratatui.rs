@@ -0,0 +1,269 @@
+//! [`ratatui_core`] backend, for text-heavy dashboards built from ratatui widgets.
+//!
+//! [`DisplayBackend`] renders cells with an [`embedded_graphics`] [`MonoFont`] onto any
+//! target of [`Color`], such as a [`crate::Display`].  Each refresh is a full refresh, so
+//! [`Backend::flush`] only finishes drawing into the buffer.  Call [`crate::Display::display`]
+//! once a frame is complete, reaching the display through [`DisplayBackend::target_mut`], e.g.
+//! as `terminal.backend_mut().target_mut()`.
+
+use crate::color::Color;
+use embedded_graphics::{
+    mono_font::{MonoFont, MonoTextStyleBuilder},
+    pixelcolor::Rgb888,
+    prelude::{DrawTarget, Point, Size},
+    primitives::Rectangle,
+    text::{Baseline, Text},
+    Drawable,
+};
+use ratatui_core::{
+    backend::{Backend, ClearType, WindowSize},
+    buffer::Cell,
+    layout::{Position, Size as TermSize},
+    style::{Color as TermColor, Modifier},
+};
+
+/// Ink for [`TermColor::Reset`] foregrounds.
+const DEFAULT_FOREGROUND: Color = Color::BLACK;
+/// Ink for [`TermColor::Reset`] backgrounds, and for cleared cells.
+const DEFAULT_BACKGROUND: Color = Color::WHITE;
+
+/// A ratatui backend drawing onto `target`.  Cells are the font's character size plus its
+/// character spacing, counted from the top left corner.
+pub struct DisplayBackend<'a, T> {
+    target: &'a mut T,
+    font: &'a MonoFont<'a>,
+    bold_font: Option<&'a MonoFont<'a>>,
+    cursor: Position,
+}
+
+impl<'a, T> DisplayBackend<'a, T>
+where
+    T: DrawTarget<Color = Color>,
+    T::Error: core::error::Error,
+{
+    pub fn new(target: &'a mut T, font: &'a MonoFont<'a>) -> Self {
+        Self {
+            target,
+            font,
+            bold_font: None,
+            cursor: Position::ORIGIN,
+        }
+    }
+
+    /// Uses `font` for cells with [`Modifier::BOLD`].  It should have the same character size
+    /// and spacing as the regular font.
+    #[must_use]
+    pub fn with_bold_font(mut self, font: &'a MonoFont<'a>) -> Self {
+        self.bold_font = Some(font);
+        self
+    }
+
+    pub fn target(&self) -> &T {
+        self.target
+    }
+
+    /// The target being drawn onto, e.g. to refresh a [`crate::Display`] once a frame is
+    /// complete.
+    pub fn target_mut(&mut self) -> &mut T {
+        self.target
+    }
+
+    fn cell_size(&self) -> Size {
+        Size::new(
+            self.font.character_size.width + self.font.character_spacing,
+            self.font.character_size.height,
+        )
+    }
+
+    /// Area of `columns` cells starting at (`x`, `y`).
+    fn cells(&self, x: u16, y: u16, columns: u16) -> Rectangle {
+        let cell = self.cell_size();
+        let top_left = Point::new(
+            i32::from(x) * i32::try_from(cell.width).unwrap_or(i32::MAX),
+            i32::from(y) * i32::try_from(cell.height).unwrap_or(i32::MAX),
+        );
+        Rectangle::new(
+            top_left,
+            Size::new(u32::from(columns) * cell.width, cell.height),
+        )
+    }
+
+    fn clear_cells(&mut self, x: u16, y: u16, columns: u16) -> Result<(), T::Error> {
+        let area = self.cells(x, y, columns);
+        self.target.fill_solid(&area, DEFAULT_BACKGROUND)
+    }
+
+    fn draw_cell(&mut self, x: u16, y: u16, cell: &Cell) -> Result<(), T::Error> {
+        let mut foreground = ink(cell.fg).unwrap_or(DEFAULT_FOREGROUND);
+        let mut background = ink(cell.bg).unwrap_or(DEFAULT_BACKGROUND);
+        if cell.modifier.contains(Modifier::REVERSED) {
+            core::mem::swap(&mut foreground, &mut background);
+        }
+
+        let area = self.cells(x, y, 1);
+        self.target.fill_solid(&area, background)?;
+        if cell.modifier.contains(Modifier::HIDDEN) {
+            return Ok(());
+        }
+
+        let font = match self.bold_font {
+            Some(bold_font) if cell.modifier.contains(Modifier::BOLD) => bold_font,
+            _ => self.font,
+        };
+        let mut style = MonoTextStyleBuilder::new()
+            .font(font)
+            .text_color(foreground);
+        if cell.modifier.contains(Modifier::UNDERLINED) {
+            style = style.underline();
+        }
+        if cell.modifier.contains(Modifier::CROSSED_OUT) {
+            style = style.strikethrough();
+        }
+        Text::with_baseline(cell.symbol(), area.top_left, style.build(), Baseline::Top)
+            .draw(self.target)?;
+        Ok(())
+    }
+}
+
+impl<T> Backend for DisplayBackend<'_, T>
+where
+    T: DrawTarget<Color = Color>,
+    T::Error: core::error::Error,
+{
+    type Error = T::Error;
+
+    fn draw<'a, I>(&mut self, content: I) -> Result<(), Self::Error>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        for (x, y, cell) in content {
+            self.draw_cell(x, y, cell)?;
+        }
+        Ok(())
+    }
+
+    /// The panel has no cursor, so its position is only tracked.
+    fn hide_cursor(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn show_cursor(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn get_cursor_position(&mut self) -> Result<Position, Self::Error> {
+        Ok(self.cursor)
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> Result<(), Self::Error> {
+        self.cursor = position.into();
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), Self::Error> {
+        self.target.clear(DEFAULT_BACKGROUND)
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> Result<(), Self::Error> {
+        let TermSize { width, height } = self.size()?;
+        let Position { x, y } = self.cursor;
+        match clear_type {
+            ClearType::All => return self.clear(),
+            ClearType::AfterCursor => {
+                self.clear_cells(x, y, width.saturating_sub(x))?;
+                for row in y.saturating_add(1)..height {
+                    self.clear_cells(0, row, width)?;
+                }
+            }
+            ClearType::BeforeCursor => {
+                for row in 0..y {
+                    self.clear_cells(0, row, width)?;
+                }
+                self.clear_cells(0, y, x.saturating_add(1).min(width))?;
+            }
+            ClearType::CurrentLine => self.clear_cells(0, y, width)?,
+            ClearType::UntilNewLine => self.clear_cells(x, y, width.saturating_sub(x))?,
+        }
+        Ok(())
+    }
+
+    fn size(&self) -> Result<TermSize, Self::Error> {
+        let size = self.target.bounding_box().size;
+        let cell = self.cell_size();
+        let columns = size.width / cell.width.max(1);
+        let rows = size.height / cell.height.max(1);
+        Ok(TermSize::new(
+            u16::try_from(columns).unwrap_or(u16::MAX),
+            u16::try_from(rows).unwrap_or(u16::MAX),
+        ))
+    }
+
+    fn window_size(&mut self) -> Result<WindowSize, Self::Error> {
+        let size = self.target.bounding_box().size;
+        Ok(WindowSize {
+            columns_rows: self.size()?,
+            pixels: TermSize::new(
+                u16::try_from(size.width).unwrap_or(u16::MAX),
+                u16::try_from(size.height).unwrap_or(u16::MAX),
+            ),
+        })
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// The ink for `color`, or `None` for [`TermColor::Reset`].  Named colors have a fixed ink,
+/// while RGB and indexed colors take the nearest one.
+fn ink(color: TermColor) -> Option<Color> {
+    Some(match color {
+        TermColor::Reset => return None,
+        TermColor::Black | TermColor::DarkGray | TermColor::Gray => Color::BLACK,
+        TermColor::Red | TermColor::LightRed | TermColor::Magenta => Color::RED,
+        TermColor::LightMagenta => Color::ORANGE,
+        TermColor::Green | TermColor::LightGreen => Color::GREEN,
+        TermColor::Yellow | TermColor::LightYellow => Color::YELLOW,
+        TermColor::Blue | TermColor::LightBlue | TermColor::Cyan | TermColor::LightCyan => {
+            Color::BLUE
+        }
+        TermColor::White => Color::WHITE,
+        TermColor::Rgb(r, g, b) => Rgb888::new(r, g, b).into(),
+        TermColor::Indexed(index) => return ink(indexed(index)),
+    })
+}
+
+/// The xterm 256-color palette.
+fn indexed(index: u8) -> TermColor {
+    const NAMED: [TermColor; 16] = [
+        TermColor::Black,
+        TermColor::Red,
+        TermColor::Green,
+        TermColor::Yellow,
+        TermColor::Blue,
+        TermColor::Magenta,
+        TermColor::Cyan,
+        TermColor::Gray,
+        TermColor::DarkGray,
+        TermColor::LightRed,
+        TermColor::LightGreen,
+        TermColor::LightYellow,
+        TermColor::LightBlue,
+        TermColor::LightMagenta,
+        TermColor::LightCyan,
+        TermColor::White,
+    ];
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    match index {
+        0..=15 => NAMED[usize::from(index)],
+        16..=231 => {
+            let cube = usize::from(index - 16);
+            TermColor::Rgb(LEVELS[cube / 36], LEVELS[cube / 6 % 6], LEVELS[cube % 6])
+        }
+        232..=255 => {
+            let level = 8 + (index - 232) * 10;
+            TermColor::Rgb(level, level, level)
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Observing the phases of a refresh.
+//!
+//! [`crate::Display::display_observed`] reports each phase to a [`RefreshObserver`] as it
+//! happens, e.g. to light a "refreshing" LED, and every refresh returns [`RefreshStats`].
+
+/// Steps of a refresh, in the order they happen.  [`Phase::Reset`] and [`Phase::Init`] are
+/// skipped when the panel is already awake, and [`Phase::Sleep`] only follows a refresh from
+/// deep sleep.
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Phase {
+    /// Pulsing RST and waiting for the controller to come out of reset.
+    Reset,
+    /// Sending the initialization sequence.
+    Init,
+    /// Sending the frame over SPI.
+    Transfer,
+    /// Waiting for the panel supplies to power on.
+    PowerOn,
+    /// Waiting for the panel to redraw.
+    Refresh,
+    /// Waiting for the panel supplies to power off.
+    PowerOff,
+    /// Putting the panel into deep sleep.
+    Sleep,
+}
+
+/// Called by the driver around each [`Phase`] of a refresh.  Both methods do nothing by
+/// default.
+pub trait RefreshObserver {
+    fn started(&mut self, _phase: Phase) {}
+
+    /// `busy_us` is how long the phase waited on BUSY, in microseconds.
+    fn finished(&mut self, _phase: Phase, _busy_us: u32) {}
+}
+
+impl RefreshObserver for () {}
+
+/// Time spent waiting on BUSY during a refresh, in microseconds.  Times are counted in
+/// [`crate::timing::Timings::busy_poll_us`] steps of the driver's delay, so they are only as
+/// fine as the polling interval.
+#[derive(Default, Debug, Eq, Hash, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RefreshStats {
+    pub reset_busy_us: u32,
+    pub power_on_busy_us: u32,
+    pub refresh_busy_us: u32,
+    pub power_off_busy_us: u32,
+}
+
+impl RefreshStats {
+    /// Sum of all BUSY waits.
+    pub fn total_busy_us(&self) -> u32 {
+        self.reset_busy_us
+            .saturating_add(self.power_on_busy_us)
+            .saturating_add(self.refresh_busy_us)
+            .saturating_add(self.power_off_busy_us)
+    }
+}
@@ -1,9 +1,9 @@
 #![allow(dead_code)]
 pub(super) const PANEL_SET_REGISTER: u8 = 0x00;
 pub(super) const POWER_SET_REGISTER: u8 = 0x01;
-pub(super) const VCM_DC_SET_REGISTER: u8 = 0x02;
+pub(super) const POWER_OFF_REGISTER: u8 = 0x02;
 pub(super) const POWER_OFF_SEQ_SET_REGISTER: u8 = 0x03;
-pub(super) const POWER_OFF_REGISTER: u8 = 0x04;
+pub(super) const POWER_ON_REGISTER: u8 = 0x04;
 pub(super) const BOOSTER_SOFTSTART_REGISTER: u8 = 0x06;
 pub(super) const DEEP_SLEEP_REGISTER: u8 = 0x07;
 
@@ -21,6 +21,44 @@ pub(super) const TEMP_SENSOR_RD_REGISTER: u8 = 0x43;
 
 pub(super) const VCOM_DATA_INTERVAL_REGISTER: u8 = 0x50;
 pub(super) const LOW_POWER_DETECT_REGISTER: u8 = 0x51;
+pub(super) const TCON_SET_REGISTER: u8 = 0x60;
 pub(super) const RESOLUTION_SET_REGISTER: u8 = 0x61;
 pub(super) const STATUS_REGISTER: u8 = 0x71;
+pub(super) const AUTO_MEASURE_VCOM_REGISTER: u8 = 0x80;
 pub(super) const VCOM_VALUE_REGISTER: u8 = 0x81;
+pub(super) const VCOM_DC_SETTING_REGISTER: u8 = 0x82;
+pub(super) const POWER_SAVING_REGISTER: u8 = 0xE3;
+pub(super) const LOW_VOLTAGE_SELECT_REGISTER: u8 = 0xE4;
+
+/// Symbolic name of `command`, for tracing.
+pub(super) fn name(command: u8) -> &'static str {
+    match command {
+        PANEL_SET_REGISTER => "PANEL_SET",
+        POWER_SET_REGISTER => "POWER_SET",
+        POWER_OFF_REGISTER => "POWER_OFF",
+        POWER_OFF_SEQ_SET_REGISTER => "POWER_OFF_SEQ_SET",
+        POWER_ON_REGISTER => "POWER_ON",
+        BOOSTER_SOFTSTART_REGISTER => "BOOSTER_SOFTSTART",
+        DEEP_SLEEP_REGISTER => "DEEP_SLEEP",
+        DATA_START_TRANS_REGISTER => "DATA_START_TRANS",
+        DATA_STOP_REGISTER => "DATA_STOP",
+        DISPLAY_REF_REGISTER => "DISPLAY_REF",
+        IMAGE_PROCESS_REGISTER => "IMAGE_PROCESS",
+        PLL_CONTROL_REGISTER => "PLL_CONTROL",
+        TEMP_SENSOR_REGISTER => "TEMP_SENSOR",
+        TEMP_SENSOR_EN_REGISTER => "TEMP_SENSOR_EN",
+        TEMP_SENSOR_WR_REGISTER => "TEMP_SENSOR_WR",
+        TEMP_SENSOR_RD_REGISTER => "TEMP_SENSOR_RD",
+        VCOM_DATA_INTERVAL_REGISTER => "VCOM_DATA_INTERVAL",
+        LOW_POWER_DETECT_REGISTER => "LOW_POWER_DETECT",
+        TCON_SET_REGISTER => "TCON_SET",
+        RESOLUTION_SET_REGISTER => "RESOLUTION_SET",
+        STATUS_REGISTER => "STATUS",
+        AUTO_MEASURE_VCOM_REGISTER => "AUTO_MEASURE_VCOM",
+        VCOM_VALUE_REGISTER => "VCOM_VALUE",
+        VCOM_DC_SETTING_REGISTER => "VCOM_DC_SETTING",
+        POWER_SAVING_REGISTER => "POWER_SAVING",
+        LOW_VOLTAGE_SELECT_REGISTER => "LOW_VOLTAGE_SELECT",
+        _ => "UNKNOWN",
+    }
+}
@@ -0,0 +1,196 @@
+//! `serde` support for types that need more than a derive.
+//!
+//! [`Color`] is a name such as `"red"` in human-readable formats like JSON, and its raw value
+//! otherwise.  Names and raw values are both accepted when deserializing.  [`PackedImage`] and
+//! [`Canvas`] are their width and packed bytes, so a whole frame costs little more than the
+//! frame itself with e.g. postcard, and a [`PackedImage`] deserializes without copying.
+//!
+//! As it borrows its bytes, a [`PackedImage`] only deserializes from formats that can hand
+//! them out of the input, such as postcard.  JSON has no byte strings, so images serialized to
+//! JSON cannot be read back.
+
+use crate::{
+    color::Color,
+    config::Vcom,
+    packed::{Canvas, PackedImage},
+};
+use core::fmt;
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+const NAMES: [(&str, Color); 7] = [
+    ("black", Color::BLACK),
+    ("white", Color::WHITE),
+    ("green", Color::GREEN),
+    ("blue", Color::BLUE),
+    ("red", Color::RED),
+    ("yellow", Color::YELLOW),
+    ("orange", Color::ORANGE),
+];
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            let (name, _) = NAMES.iter().find(|(_, color)| color == self).unwrap();
+            serializer.serialize_str(name)
+        } else {
+            serializer.serialize_u8((*self).into())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ColorVisitor;
+
+        impl Visitor<'_> for ColorVisitor {
+            type Value = Color;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a color name or value")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Color, E> {
+                u8::try_from(value)
+                    .ok()
+                    .and_then(|value| Color::try_from(value).ok())
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(value), &self))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Color, E> {
+                NAMES
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(value))
+                    .map(|&(_, color)| color)
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Str(value), &self))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(ColorVisitor)
+        } else {
+            deserializer.deserialize_u8(ColorVisitor)
+        }
+    }
+}
+
+/// In millivolts.
+impl Serialize for Vcom {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i16(self.millivolts())
+    }
+}
+
+impl<'de> Deserialize<'de> for Vcom {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let millivolts = i16::deserialize(deserializer)?;
+        Vcom::from_millivolts(millivolts).ok_or_else(|| {
+            de::Error::invalid_value(
+                de::Unexpected::Signed(millivolts.into()),
+                &"a VCOM from -4000 to -100 mV",
+            )
+        })
+    }
+}
+
+/// Serialized as bytes rather than a sequence of `u8`.
+struct Bytes<'a>(&'a [u8]);
+
+impl Serialize for Bytes<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+const FIELDS: &[&str] = &["width", "data"];
+
+fn serialize_image<S: Serializer>(
+    image: &PackedImage<'_>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut state = serializer.serialize_struct("PackedImage", 2)?;
+    state.serialize_field("width", &image.width())?;
+    state.serialize_field("data", &Bytes(image.data()))?;
+    state.end()
+}
+
+impl Serialize for PackedImage<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_image(self, serializer)
+    }
+}
+
+/// Serialized like [`PackedImage`].
+impl<B> Serialize for Canvas<B>
+where
+    B: AsRef<[u8]>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_image(&self.as_image(), serializer)
+    }
+}
+
+/// Borrows the packed bytes from the input, which must therefore hold them as bytes.
+impl<'de: 'a, 'a> Deserialize<'de> for PackedImage<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(field_identifier, rename_all = "lowercase")]
+        enum Field {
+            Width,
+            Data,
+        }
+
+        struct ImageVisitor;
+
+        impl<'de> Visitor<'de> for ImageVisitor {
+            type Value = PackedImage<'de>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a packed image")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let width = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let data = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                image(width, data)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let (mut width, mut data) = (None, None);
+                while let Some(key) = map.next_key()? {
+                    match key {
+                        Field::Width => width = Some(map.next_value()?),
+                        Field::Data => data = Some(map.next_value()?),
+                    }
+                }
+                image(
+                    width.ok_or_else(|| de::Error::missing_field("width"))?,
+                    data.ok_or_else(|| de::Error::missing_field("data"))?,
+                )
+            }
+        }
+
+        deserializer.deserialize_struct("PackedImage", FIELDS, ImageVisitor)
+    }
+}
+
+fn image<E: de::Error>(width: usize, data: &[u8]) -> Result<PackedImage<'_>, E> {
+    let whole_rows = match width.div_ceil(2) {
+        0 => data.is_empty(),
+        stride => data.len().is_multiple_of(stride),
+    };
+    if !whole_rows {
+        return Err(E::invalid_length(
+            data.len(),
+            &"whole rows of packed pixels",
+        ));
+    }
+    Ok(PackedImage::new(data, width))
+}
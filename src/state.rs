@@ -0,0 +1,31 @@
+//! Power states of the panel, tracked by [`crate::Display`] at the type level.
+//!
+//! ```text
+//! Uninitialized --init--> Asleep --wake--> Awake
+//!                           ^                 |
+//!                           +------sleep------+
+//! ```
+
+/// Freshly constructed.  The controller has not been configured yet.
+pub struct Uninitialized;
+
+/// Initialized and in deep sleep.  Waking requires a panel reset.
+pub struct Asleep;
+
+/// Reset and configured.  Refreshes can be issued back to back.
+pub struct Awake;
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for super::Uninitialized {}
+    impl Sealed for super::Asleep {}
+    impl Sealed for super::Awake {}
+}
+
+/// Implemented by all power states.  This trait is sealed.
+pub trait PowerState: sealed::Sealed {}
+
+impl PowerState for Uninitialized {}
+impl PowerState for Asleep {}
+impl PowerState for Awake {}
@@ -1,6 +1,8 @@
 extern crate std;
 use super::*;
+use embedded_hal::spi;
 use embedded_hal_mock::eh1::{pin::State as PinState, top_level::Hal};
+use interface::FourWire;
 
 #[test]
 fn test_reset_panel() {
@@ -25,6 +27,34 @@ fn test_reset_panel() {
     hal.done();
 }
 
+#[test]
+fn test_reset_panel_timings() {
+    let mut hal = Hal::new(&[]);
+
+    let spi = hal.clone().spi();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let rst = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    hal.update_expectations(&[
+        rst.expect_set(PinState::Low),
+        delay.expect_delay_ns(10_000),
+        rst.expect_set(PinState::High),
+        delay.expect_delay_ns(50_000_000),
+    ]);
+
+    let mut epd = Display::new(spi, rst, dc, busy, delay);
+    epd.set_timings(Timings {
+        reset_pulse_us: 10,
+        reset_wait_us: 50_000,
+        ..Timings::default()
+    });
+
+    epd.reset_panel().unwrap();
+    hal.done();
+}
+
 #[test]
 fn test_send_command() {
     let mut hal = Hal::new(&[]);
@@ -74,7 +104,7 @@ fn test_send_data() {
 }
 
 #[test]
-fn test_sleep_panel() {
+fn test_deep_sleep_panel() {
     let mut hal = Hal::new(&[]);
 
     let spi = hal.clone().spi();
@@ -98,7 +128,9 @@ fn test_sleep_panel() {
         dc.expect_set(PinState::Low),
     ]);
 
-    Display::new(spi, rst, dc, busy, delay).sleep().unwrap();
+    Display::new(spi, rst, dc, busy, delay)
+        .deep_sleep()
+        .unwrap();
 
     hal.done();
 }
@@ -122,7 +154,9 @@ fn test_wakeup() {
         // busy
         // no longer busy
         busy.expect_get(PinState::Low),
+        delay.expect_delay_ns(1_000_000),
         busy.expect_get(PinState::Low),
+        delay.expect_delay_ns(1_000_000),
         busy.expect_get(PinState::High),
         // self.send_command(registers::PANEL_SET_REGISTER)?;
         // self.send_data(&[0xEF, 0x08])?;
@@ -214,7 +248,7 @@ fn test_wakeup() {
         spi.expect_transaction_start(),
         spi.expect_write_vec([0xAA].to_vec()),
         spi.expect_transaction_end(),
-        // self.delay.delay_ms(100u32);
+        // self.delay.delay_us(self.timings.vcom_setup_us);
         // self.send_command(registers::VCOM_DATA_INTERVAL_REGISTER)?;
         // self.send_data(&[0x37])?;
         delay.expect_delay_ns(100_000_000),
@@ -228,7 +262,9 @@ fn test_wakeup() {
         spi.expect_transaction_end(),
     ]);
 
-    Display::new(spi, rst, dc, busy, delay).wakeup().unwrap();
+    Display::new(spi, rst, dc, busy, delay)
+        .wakeup(&mut (), &mut RefreshStats::default())
+        .unwrap();
 
     hal.done();
 }
@@ -253,7 +289,9 @@ fn test_init() {
         // busy
         // no longer busy
         busy.expect_get(PinState::Low),
+        delay.expect_delay_ns(1_000_000),
         busy.expect_get(PinState::Low),
+        delay.expect_delay_ns(1_000_000),
         busy.expect_get(PinState::High),
         // self.send_command(registers::PANEL_SET_REGISTER)?;
         // self.send_data(&[0xEF, 0x08])?;
@@ -345,7 +383,7 @@ fn test_init() {
         spi.expect_transaction_start(),
         spi.expect_write_vec([0xAA].to_vec()),
         spi.expect_transaction_end(),
-        // self.delay.delay_ms(100u32);
+        // self.delay.delay_us(self.timings.vcom_setup_us);
         // self.send_command(registers::VCOM_DATA_INTERVAL_REGISTER)?;
         // self.send_data(&[0x37])?;
         delay.expect_delay_ns(100_000_000),
@@ -397,7 +435,9 @@ fn test_display() {
         // busy
         // no longer busy
         busy.expect_get(PinState::Low),
+        delay.expect_delay_ns(1_000_000),
         busy.expect_get(PinState::Low),
+        delay.expect_delay_ns(1_000_000),
         busy.expect_get(PinState::High),
         // self.send_command(registers::PANEL_SET_REGISTER)?;
         // self.send_data(&[0xEF, 0x08])?;
@@ -489,7 +529,7 @@ fn test_display() {
         spi.expect_transaction_start(),
         spi.expect_write_vec([0xAA].to_vec()),
         spi.expect_transaction_end(),
-        // self.delay.delay_ms(100u32);
+        // self.delay.delay_us(self.timings.vcom_setup_us);
         // self.send_command(registers::VCOM_DATA_INTERVAL_REGISTER)?;
         // self.send_data(&[0x37])?;
         delay.expect_delay_ns(100_000_000),
@@ -528,13 +568,16 @@ fn test_display() {
         spi.expect_write(0x12),
         spi.expect_transaction_end(),
         busy.expect_get(PinState::Low),
+        delay.expect_delay_ns(1_000_000),
         busy.expect_get(PinState::High),
         dc.expect_set(PinState::Low),
         spi.expect_transaction_start(),
         spi.expect_write(0x04),
         spi.expect_transaction_end(),
         busy.expect_get(PinState::High),
+        delay.expect_delay_ns(1_000_000),
         busy.expect_get(PinState::High),
+        delay.expect_delay_ns(1_000_000),
         busy.expect_get(PinState::Low),
         delay.expect_delay_ns(200_000_000),
         // sleep
@@ -553,12 +596,15 @@ fn test_display() {
     ]);
 
     Display {
-        spi,
+        interface: FourWire { spi, dc },
         rst,
-        dc,
         busy,
         delay,
         buffer: [0b0001_0001; (WIDTH * HEIGHT) / 2],
+        chunk_size: FRAME_CHUNK,
+        timings: Timings::default(),
+        config: PanelConfig::default(),
+        state: PhantomData::<state::Asleep>,
     }
     .display()
     .unwrap();
@@ -593,3 +639,1881 @@ fn test_set_pixel() {
 
     hal.done();
 }
+
+#[test]
+fn test_awake_display() {
+    let mut hal = Hal::new(&[]);
+
+    let spi = hal.clone().spi();
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    hal.update_expectations(&[
+        // no reset or wakeup
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x61),
+        spi.expect_transaction_end(),
+        dc.expect_set(PinState::High),
+        spi.expect_transaction_start(),
+        spi.expect_write_vec([0x02, 0x58, 0x01, 0xc0].to_vec()),
+        spi.expect_transaction_end(),
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x10),
+        spi.expect_transaction_end(),
+        dc.expect_set(PinState::High),
+        spi.expect_transaction_start(),
+        spi.expect_write_vec([0b00010001; super::WIDTH * super::HEIGHT / 2].to_vec()),
+        spi.expect_transaction_end(),
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x04),
+        spi.expect_transaction_end(),
+        busy.expect_get(PinState::High),
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x12),
+        spi.expect_transaction_end(),
+        busy.expect_get(PinState::High),
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x04),
+        spi.expect_transaction_end(),
+        busy.expect_get(PinState::Low),
+        delay.expect_delay_ns(200_000_000),
+        // no sleep
+    ]);
+
+    Display {
+        interface: FourWire { spi, dc },
+        rst,
+        busy,
+        delay,
+        buffer: [0b0001_0001; (WIDTH * HEIGHT) / 2],
+        chunk_size: FRAME_CHUNK,
+        timings: Timings::default(),
+        config: PanelConfig::default(),
+        state: PhantomData::<state::Awake>,
+    }
+    .display()
+    .unwrap();
+
+    hal.done();
+}
+
+#[test]
+fn test_set_frame_rate() {
+    use config::FrameRate;
+
+    let mut hal = Hal::new(&[]);
+
+    let spi = hal.clone().spi();
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    hal.update_expectations(&[
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x30),
+        spi.expect_transaction_end(),
+        dc.expect_set(PinState::High),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x3A),
+        spi.expect_transaction_end(),
+    ]);
+
+    let mut epd = Display {
+        interface: FourWire { spi, dc },
+        rst,
+        busy,
+        delay,
+        buffer: [0b0001_0001; (WIDTH * HEIGHT) / 2],
+        chunk_size: FRAME_CHUNK,
+        timings: Timings::default(),
+        config: PanelConfig::default(),
+        state: PhantomData::<state::Awake>,
+    };
+    epd.set_frame_rate(FrameRate::Hz100).unwrap();
+    assert_eq!(epd.config.frame_rate, Some(FrameRate::Hz100));
+    assert_eq!(FrameRate::Hz50.bits(), 0x3C);
+
+    hal.done();
+}
+
+#[test]
+fn test_sleep() {
+    let mut hal = Hal::new(&[]);
+
+    let spi = hal.clone().spi();
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    hal.update_expectations(&[
+        delay.expect_delay_ns(10_000_000),
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x07),
+        spi.expect_transaction_end(),
+        dc.expect_set(PinState::High),
+        spi.expect_transaction_start(),
+        spi.expect_write(0xA5),
+        spi.expect_transaction_end(),
+        delay.expect_delay_ns(100_000_000),
+        rst.expect_set(PinState::Low),
+        dc.expect_set(PinState::Low),
+    ]);
+
+    let _asleep: Display<_, _, _, _, state::Asleep> = Display {
+        interface: FourWire { spi, dc },
+        rst,
+        busy,
+        delay,
+        buffer: [0b0001_0001; (WIDTH * HEIGHT) / 2],
+        chunk_size: FRAME_CHUNK,
+        timings: Timings::default(),
+        config: PanelConfig::default(),
+        state: PhantomData::<state::Awake>,
+    }
+    .sleep()
+    .unwrap();
+
+    hal.done();
+}
+
+#[test]
+fn test_failed_transition() {
+    let mut hal = Hal::new(&[]);
+
+    let spi = hal.clone().spi();
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    hal.update_expectations(&[
+        rst.expect_set(PinState::Low),
+        delay.expect_delay_ns(1_000_000),
+        rst.expect_set(PinState::High),
+        delay.expect_delay_ns(200_000_000),
+        busy.expect_get(PinState::Low),
+    ]);
+
+    let asleep: Display<_, _, _, _, state::Asleep> = Display {
+        interface: FourWire { spi, dc },
+        rst,
+        busy,
+        delay,
+        buffer: [0b0001_0001; (WIDTH * HEIGHT) / 2],
+        chunk_size: FRAME_CHUNK,
+        timings: Timings {
+            busy_timeout_us: Some(0),
+            ..Timings::default()
+        },
+        config: PanelConfig::default(),
+        state: PhantomData,
+    };
+    let Err(failed) = asleep.wake() else {
+        panic!("waking should time out");
+    };
+    assert_eq!(failed.error, error::Error::BusyTimeout);
+    assert_eq!(std::format!("{failed}"), "timed out waiting on BUSY");
+    // the display comes back still asleep, so its resources can be recovered
+    let parts = failed.display.release();
+    assert_eq!(parts.timings.busy_timeout_us, Some(0));
+    hal.done();
+
+    let failed = error::TransitionError {
+        display: (),
+        error: error::Error::<
+            embedded_hal::digital::ErrorKind,
+            embedded_hal::digital::ErrorKind,
+            embedded_hal::digital::ErrorKind,
+            _,
+        >::Spi(spi::ErrorKind::Other),
+    };
+    assert_eq!(
+        error::ErasedError::from(failed),
+        error::ErasedError::Spi(spi::ErrorKind::Other)
+    );
+}
+
+#[test]
+fn test_release() {
+    let mut hal = Hal::new(&[]);
+
+    let spi = hal.clone().spi();
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    let mut epd = Display {
+        interface: FourWire { spi, dc },
+        rst,
+        busy,
+        delay,
+        buffer: [0b0001_0001; (WIDTH * HEIGHT) / 2],
+        chunk_size: FRAME_CHUNK,
+        timings: Timings::default(),
+        config: PanelConfig::default(),
+        state: PhantomData::<state::Asleep>,
+    };
+    epd.set_pixel(0, 0, super::color::Color::RED).unwrap();
+    epd.set_chunk_size(NonZeroUsize::new(4_096).unwrap());
+    let timings = Timings {
+        busy_timeout_us: Some(30_000_000),
+        ..Timings::default()
+    };
+    epd.set_timings(timings);
+    let config = PanelConfig {
+        verify_transfer: true,
+        transfer_retries: 2,
+        ..PanelConfig::default()
+    };
+    epd.set_config(config);
+
+    let parts = epd.release();
+    assert_eq!(parts.buffer[0], 0b01000001);
+
+    let epd = Display::from_parts(parts);
+    assert_eq!(epd.buffer[0], 0b01000001);
+    assert_eq!(epd.chunk_size.get(), 4_096);
+    assert_eq!(epd.timings, timings);
+    assert_eq!(epd.config, config);
+
+    hal.done();
+}
+
+#[test]
+fn test_pack() {
+    let mut packed = [0; 9];
+
+    assert_eq!(interface::pack(false, &[0x07], &mut packed), 2);
+    assert_eq!(packed[..2], [0b0000_0011, 0b1000_0000]);
+
+    assert_eq!(interface::pack(true, &[0xA5], &mut packed), 2);
+    assert_eq!(packed[..2], [0b1101_0010, 0b1000_0000]);
+
+    assert_eq!(interface::pack(true, &[0xFF; 8], &mut packed), 9);
+    assert_eq!(packed, [0xFF; 9]);
+}
+
+#[test]
+fn test_three_wire_packed() {
+    let mut hal = Hal::new(&[]);
+
+    let spi = hal.clone().spi();
+    let rst = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    hal.update_expectations(&[
+        delay.expect_delay_ns(10_000_000),
+        spi.expect_transaction_start(),
+        spi.expect_write_vec([0b0000_0011, 0b1000_0000].to_vec()),
+        spi.expect_transaction_end(),
+        spi.expect_transaction_start(),
+        spi.expect_write_vec([0b1101_0010, 0b1000_0000].to_vec()),
+        spi.expect_transaction_end(),
+        delay.expect_delay_ns(100_000_000),
+        rst.expect_set(PinState::Low),
+    ]);
+
+    Display::with_interface(interface::ThreeWirePacked::new(spi), rst, busy, delay)
+        .deep_sleep()
+        .unwrap();
+
+    hal.done();
+}
+
+#[test]
+fn test_chunked_refresh() {
+    let mut hal = Hal::new(&[]);
+
+    let spi = hal.clone().spi();
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    let mut expectations = std::vec![
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x61),
+        spi.expect_transaction_end(),
+        dc.expect_set(PinState::High),
+        spi.expect_transaction_start(),
+        spi.expect_write_vec([0x02, 0x58, 0x01, 0xc0].to_vec()),
+        spi.expect_transaction_end(),
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x10),
+        spi.expect_transaction_end(),
+    ];
+    for _ in 0..28 {
+        expectations.extend([
+            dc.expect_set(PinState::High),
+            spi.expect_transaction_start(),
+            spi.expect_write_vec([0b00010001; 4800].to_vec()),
+            spi.expect_transaction_end(),
+        ]);
+    }
+    expectations.extend([
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x04),
+        spi.expect_transaction_end(),
+        busy.expect_get(PinState::High),
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x12),
+        spi.expect_transaction_end(),
+        busy.expect_get(PinState::High),
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x04),
+        spi.expect_transaction_end(),
+        busy.expect_get(PinState::Low),
+        delay.expect_delay_ns(200_000_000),
+    ]);
+    hal.update_expectations(&expectations);
+
+    let mut epd = Display::new(spi, rst, dc, busy, delay);
+    epd.set_chunk_size(NonZeroUsize::new(4800).unwrap());
+    epd.refresh(&mut (), &mut RefreshStats::default()).unwrap();
+
+    hal.done();
+}
+
+#[test]
+fn test_refresh_with() {
+    let mut hal = Hal::new(&[]);
+
+    let spi = hal.clone().spi();
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    let mut expectations = std::vec![
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x61),
+        spi.expect_transaction_end(),
+        dc.expect_set(PinState::High),
+        spi.expect_transaction_start(),
+        spi.expect_write_vec([0x02, 0x58, 0x01, 0xc0].to_vec()),
+        spi.expect_transaction_end(),
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x10),
+        spi.expect_transaction_end(),
+    ];
+    // 134_400 bytes in chunks of 50_000
+    for (offset, len) in [(0, 50_000), (50_000, 50_000), (100_000, 34_400)] {
+        expectations.extend([
+            dc.expect_set(PinState::High),
+            spi.expect_transaction_start(),
+            spi.expect_write_vec(std::vec![(offset / 50_000) as u8; len]),
+            spi.expect_transaction_end(),
+        ]);
+    }
+    expectations.extend([
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x04),
+        spi.expect_transaction_end(),
+        busy.expect_get(PinState::High),
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x12),
+        spi.expect_transaction_end(),
+        busy.expect_get(PinState::High),
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x04),
+        spi.expect_transaction_end(),
+        busy.expect_get(PinState::Low),
+        delay.expect_delay_ns(200_000_000),
+    ]);
+    hal.update_expectations(&expectations);
+
+    struct Started(std::vec::Vec<refresh::Phase>);
+
+    impl refresh::RefreshObserver for Started {
+        fn started(&mut self, phase: refresh::Phase) {
+            self.0.push(phase);
+        }
+    }
+
+    let mut chunk = [0; 50_000];
+    let mut started = Started(std::vec::Vec::new());
+    Display::new(spi, rst, dc, busy, delay)
+        .refresh_with(
+            &mut chunk,
+            |offset, chunk| {
+                chunk.fill((offset / 50_000) as u8);
+            },
+            &mut started,
+            &mut RefreshStats::default(),
+        )
+        .unwrap();
+    assert_eq!(
+        started.0,
+        [
+            refresh::Phase::Transfer,
+            refresh::Phase::PowerOn,
+            refresh::Phase::Refresh,
+            refresh::Phase::PowerOff,
+        ]
+    );
+
+    hal.done();
+}
+
+#[test]
+fn test_busy_timeout() {
+    let mut hal = Hal::new(&[]);
+
+    let spi = hal.clone().spi();
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    hal.update_expectations(&[
+        busy.expect_get(PinState::Low),
+        delay.expect_delay_ns(1_000_000),
+        busy.expect_get(PinState::Low),
+        delay.expect_delay_ns(1_000_000),
+        busy.expect_get(PinState::Low),
+    ]);
+
+    let mut epd = Display::new(spi, rst, dc, busy, delay);
+    epd.set_timings(Timings {
+        busy_timeout_us: Some(2_000),
+        ..Timings::default()
+    });
+
+    let error = epd.wait_while_busy(false).unwrap_err();
+    assert_eq!(error, error::Error::BusyTimeout);
+    assert_eq!(error.kind(), error::ErrorKind::Timeout);
+
+    hal.done();
+}
+
+#[test]
+fn test_erased_error() {
+    use embedded_hal::digital;
+
+    let error: error::Error<
+        digital::ErrorKind,
+        digital::ErrorKind,
+        digital::ErrorKind,
+        spi::ErrorKind,
+    > = error::Error::Spi(spi::ErrorKind::Overrun);
+    assert_eq!(error.kind(), error::ErrorKind::Bus);
+
+    let erased: error::ErasedError = error.into();
+    assert_eq!(erased, error::ErasedError::Spi(spi::ErrorKind::Overrun));
+    assert_eq!(
+        std::format!("{erased}"),
+        "SPI error: The peripheral receive buffer was overrun"
+    );
+}
+
+#[cfg(feature = "qoi")]
+const QOI: [u8; 35] = [
+    b'q', b'o', b'i', b'f', 0, 0, 0, 3, 0, 0, 0, 2, 3, 0,
+    // QOI_OP_RGB, QOI_OP_RUN, QOI_OP_RGB
+    0xfe, 0xff, 0x00, 0x00, 0xc0, 0xfe, 0xff, 0xff, 0xff,
+    // QOI_OP_INDEX, QOI_OP_DIFF, QOI_OP_LUMA
+    0x32, 0x5e, 0xaa, 0x5a, // end marker
+    0, 0, 0, 0, 0, 0, 0, 1,
+];
+
+#[cfg(feature = "qoi")]
+#[test]
+fn test_qoi() {
+    use decode::{qoi::QoiDecoder, Decoder};
+    use embedded_graphics_core::{pixelcolor::Rgb888, prelude::Size};
+
+    let mut decoder = QoiDecoder::new(&QOI).unwrap();
+    assert_eq!(decoder.size(), Size::new(3, 2));
+
+    let mut row = [Rgb888::new(0, 0, 0); 3];
+    decoder.read_row(&mut row).unwrap();
+    assert_eq!(
+        row,
+        [
+            Rgb888::new(255, 0, 0),
+            Rgb888::new(255, 0, 0),
+            Rgb888::new(255, 255, 255)
+        ]
+    );
+    // clipped
+    let mut row = [Rgb888::new(0, 0, 0); 2];
+    decoder.read_row(&mut row).unwrap();
+    assert_eq!(row, [Rgb888::new(255, 0, 0), Rgb888::new(254, 1, 0)]);
+
+    assert_eq!(
+        QoiDecoder::new(&QOI[1..]).err(),
+        Some(decode::qoi::Error::InvalidHeader)
+    );
+    assert_eq!(
+        QoiDecoder::new(&QOI[..20]).unwrap().read_row(&mut row),
+        Err(decode::qoi::Error::Truncated)
+    );
+}
+
+#[cfg(feature = "qoi")]
+#[test]
+fn test_draw_decoded() {
+    let mut hal = Hal::new(&[]);
+
+    let spi = hal.clone().spi();
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    let mut epd = Display::new(spi, rst, dc, busy, delay);
+    let mut decoder = decode::qoi::QoiDecoder::new(&QOI).unwrap();
+    epd.draw_decoded(&mut decoder, &mut quantize::Nearest, 1, 0)
+        .unwrap();
+
+    assert_eq!(epd.buffer[0..2], [0b0001_0100, 0b0100_0001]);
+    assert_eq!(epd.buffer[300..302], [0b0001_0100, 0b0100_0000]);
+
+    hal.done();
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn test_png_rgb() {
+    use decode::{png::PngDecoder, Decoder};
+    use embedded_graphics_core::{pixelcolor::Rgb888, prelude::Size};
+
+    // 3x4 8-bit RGB using Sub, Up, Average and Paeth filters with IDAT split in two
+    let png = [
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+        0x52, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x04, 0x08, 0x02, 0x00, 0x00, 0x00, 0xc4,
+        0x4f, 0x12, 0x50, 0x00, 0x00, 0x00, 0x05, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0xfc,
+        0xcf, 0x35, 0x84, 0xaf, 0xd3, 0x00, 0x00, 0x00, 0x27, 0x49, 0x44, 0x41, 0x54, 0xc0, 0xc0,
+        0x08, 0xc6, 0x4c, 0x0c, 0xff, 0xff, 0x33, 0x30, 0x02, 0x09, 0x46, 0xe6, 0xee, 0xa9, 0xf3,
+        0x95, 0x35, 0x74, 0x37, 0xef, 0xb0, 0x61, 0xd9, 0x17, 0x20, 0xc2, 0xc0, 0xf0, 0xcd, 0x62,
+        0xce, 0x32, 0x00, 0x18, 0x5f, 0x0e, 0x78, 0xed, 0xf5, 0x4d, 0x94, 0x00, 0x00, 0x00, 0x00,
+        0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+    ];
+
+    let mut decoder = PngDecoder::new(&png).unwrap();
+    assert_eq!(decoder.size(), Size::new(3, 4));
+
+    let mut row = [Rgb888::new(0, 0, 0); 3];
+    for expected in [
+        [(255, 0, 0), (0, 255, 0), (0, 0, 255)],
+        [(255, 255, 255), (0, 0, 0), (255, 255, 0)],
+        [(10, 20, 30), (40, 50, 60), (70, 80, 90)],
+        [(200, 100, 50), (200, 100, 50), (0, 0, 0)],
+    ] {
+        decoder.read_row(&mut row).unwrap();
+        assert_eq!(row, expected.map(|(r, g, b)| Rgb888::new(r, g, b)));
+    }
+
+    // a chunk length that would overflow
+    let mut malformed = png;
+    malformed[8..12].copy_from_slice(&[0xff; 4]);
+    assert!(matches!(
+        PngDecoder::new(&malformed),
+        Err(decode::png::Error::Truncated)
+    ));
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn test_png_palette() {
+    use decode::{png::PngDecoder, Decoder};
+    use embedded_graphics_core::pixelcolor::Rgb888;
+
+    // 4x2 2-bit palette of black, red, blue and white
+    let png = [
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+        0x52, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x02, 0x02, 0x03, 0x00, 0x00, 0x00, 0x02,
+        0xc6, 0x95, 0xf0, 0x00, 0x00, 0x00, 0x0c, 0x50, 0x4c, 0x54, 0x45, 0x00, 0x00, 0x00, 0xff,
+        0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xa2, 0x1d, 0x1b, 0x3e, 0x00, 0x00, 0x00,
+        0x0c, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0x90, 0x66, 0x78, 0x02, 0x00, 0x01, 0x39,
+        0x01, 0x00, 0x7b, 0x99, 0x42, 0x37, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae,
+        0x42, 0x60, 0x82,
+    ];
+    let black = Rgb888::new(0, 0, 0);
+    let red = Rgb888::new(255, 0, 0);
+    let blue = Rgb888::new(0, 0, 255);
+    let white = Rgb888::new(255, 255, 255);
+
+    let mut decoder = PngDecoder::new(&png).unwrap();
+    let mut row = [black; 4];
+    decoder.read_row(&mut row).unwrap();
+    assert_eq!(row, [black, red, blue, white]);
+    decoder.read_row(&mut row).unwrap();
+    assert_eq!(row, [white, blue, red, black]);
+}
+
+#[cfg(feature = "graphics")]
+#[test]
+fn test_floyd_steinberg() {
+    use embedded_graphics_core::pixelcolor::{Rgb888, RgbColor};
+    use quantize::{FloydSteinberg, Quantizer};
+
+    let mut quantizer = FloydSteinberg::new();
+    let gray = Rgb888::new(128, 128, 128);
+    let mut sum = [0u32; 3];
+    for y in 0..16 {
+        for x in 0..16 {
+            let color: Rgb888 = quantizer.quantize(x, y, gray).into();
+            sum[0] += u32::from(color.r());
+            sum[1] += u32::from(color.g());
+            sum[2] += u32::from(color.b());
+        }
+    }
+
+    // the nearest ink is orange but the average over an area should be close to gray
+    for channel in sum {
+        assert!((96..=160).contains(&(channel / 256)));
+    }
+}
+
+#[test]
+fn test_draw_packed() {
+    let mut hal = Hal::new(&[]);
+
+    let spi = hal.clone().spi();
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    let mut epd = Display::new(spi, rst, dc, busy, delay);
+
+    // 3x2: red green blue / yellow orange black
+    let image = packed::PackedImage::new(&[0x42, 0x30, 0x56, 0x00], 3);
+    assert_eq!(image.height(), 2);
+    assert_eq!(image.pixel(2, 1), Some(color::Color::BLACK));
+    assert_eq!(image.pixel(3, 1), None);
+
+    epd.draw_packed(&image, 2, 0);
+    assert_eq!(epd.buffer[0..4], [0x11, 0x42, 0x31, 0x11]);
+    assert_eq!(epd.buffer[300..304], [0x11, 0x56, 0x01, 0x11]);
+
+    epd.draw_packed(&image, 7, 1);
+    assert_eq!(epd.buffer[303..306], [0x14, 0x23, 0x11]);
+    assert_eq!(epd.buffer[603..606], [0x15, 0x60, 0x11]);
+
+    // clipped at the right and bottom edges
+    epd.draw_packed(&image, WIDTH - 2, HEIGHT - 1);
+    assert_eq!(epd.buffer[epd.buffer.len() - 1], 0x42);
+
+    // entirely off the panel
+    let before = epd.buffer;
+    epd.draw_packed(&image, WIDTH + 2, 0);
+    assert_eq!(epd.buffer, before);
+
+    hal.done();
+}
+
+#[cfg(feature = "graphics")]
+#[test]
+fn test_packed_image_drawable() {
+    use embedded_graphics_core::{
+        image::ImageDrawable,
+        prelude::{Point, Size},
+        primitives::Rectangle,
+    };
+
+    let mut hal = Hal::new(&[]);
+
+    let spi = hal.clone().spi();
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    let mut epd = Display::new(spi, rst, dc, busy, delay);
+    let image = packed::PackedImage::new(&[0x42, 0x30, 0x56, 0x00], 3);
+
+    image.draw(&mut epd).unwrap();
+    assert_eq!(epd.buffer[0..2], [0x42, 0x31]);
+    assert_eq!(epd.buffer[300..302], [0x56, 0x01]);
+
+    image
+        .draw_sub_image(&mut epd, &Rectangle::new(Point::new(1, 1), Size::new(2, 1)))
+        .unwrap();
+    assert_eq!(epd.buffer[0..2], [0x60, 0x31]);
+
+    hal.done();
+}
+
+#[test]
+fn test_save_area() {
+    let mut hal = Hal::new(&[]);
+
+    let spi = hal.clone().spi();
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    let mut epd = Display::new(spi, rst, dc, busy, delay);
+    epd.buffer[0..3].copy_from_slice(&[0x23, 0x45, 0x61]);
+    epd.buffer[300..303].copy_from_slice(&[0x65, 0x43, 0x21]);
+
+    let mut saved = [0; 4];
+    let image = epd.save_area(1, 0, 3, 2, &mut saved);
+    assert_eq!((image.width(), image.height()), (3, 2));
+    assert_eq!(image.row(0), [0x34, 0x50]);
+    assert_eq!(image.row(1), [0x54, 0x30]);
+
+    let overlay = packed::PackedImage::new(&[0x00, 0x00, 0x00, 0x00], 3);
+    epd.draw_packed(&overlay, 1, 0);
+    assert_eq!(epd.buffer[0..3], [0x20, 0x00, 0x61]);
+
+    epd.draw_packed(&packed::PackedImage::new(&saved, 3), 1, 0);
+    assert_eq!(epd.buffer[0..3], [0x23, 0x45, 0x61]);
+    assert_eq!(epd.buffer[300..303], [0x65, 0x43, 0x21]);
+
+    // clipped to the panel
+    let mut saved = [0; 4];
+    let image = epd.save_area(WIDTH - 1, HEIGHT - 1, 3, 2, &mut saved);
+    assert_eq!((image.width(), image.height()), (1, 1));
+    assert_eq!(image.row(0), [0x10]);
+
+    hal.done();
+}
+
+#[test]
+fn test_draw_packed_keyed() {
+    let mut hal = Hal::new(&[]);
+
+    let spi = hal.clone().spi();
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    let mut epd = Display::new(spi, rst, dc, busy, delay);
+    let image = packed::PackedImage::new(&[0x40, 0x20], 3);
+
+    epd.draw_packed_keyed(&image, 1, 0, color::Color::BLACK);
+    assert_eq!(epd.buffer[0..2], [0x14, 0x12]);
+
+    hal.done();
+}
+
+#[test]
+fn test_copy_area() {
+    let mut hal = Hal::new(&[]);
+
+    let spi = hal.clone().spi();
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    let mut epd = Display::new(spi, rst, dc, busy, delay);
+    epd.buffer[0..3].copy_from_slice(&[0x23, 0x45, 0x61]);
+
+    // overlapping, one pixel to the right
+    epd.copy_area(0, 0, 4, 1, 1, 0);
+    assert_eq!(epd.buffer[0..3], [0x22, 0x34, 0x51]);
+
+    // overlapping, one row down
+    epd.copy_area(0, 0, 6, 2, 0, 1);
+    assert_eq!(epd.buffer[300..303], [0x22, 0x34, 0x51]);
+    assert_eq!(epd.buffer[600..603], [0x11, 0x11, 0x11]);
+
+    // aligned copy to another byte
+    epd.copy_area(0, 0, 4, 1, 4, 3);
+    assert_eq!(epd.buffer[900..905], [0x11, 0x11, 0x22, 0x34, 0x11]);
+
+    hal.done();
+}
+
+#[test]
+fn test_canvas() {
+    let mut hal = Hal::new(&[]);
+
+    let spi = hal.clone().spi();
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    let mut epd = Display::new(spi, rst, dc, busy, delay);
+    let mut canvas = packed::Canvas::new([0; 6], 5);
+    assert_eq!((canvas.width(), canvas.height()), (5, 2));
+
+    canvas.clear(color::Color::GREEN);
+    assert!(canvas.set_pixel(0, 0, color::Color::RED));
+    assert!(canvas.set_pixel(4, 1, color::Color::BLUE));
+    assert!(!canvas.set_pixel(5, 0, color::Color::BLUE));
+    assert_eq!(canvas.pixel(4, 1), Some(color::Color::BLUE));
+
+    // overlapping, two pixels to the right
+    canvas.copy_area(0, 0, 3, 1, 2, 0);
+    assert_eq!(canvas.into_inner()[0..3], [0x42, 0x42, 0x22]);
+
+    epd.draw_packed(&canvas.as_image(), 1, 0);
+    assert_eq!(epd.buffer[0..3], [0x14, 0x24, 0x22]);
+    assert_eq!(epd.buffer[300..303], [0x12, 0x22, 0x23]);
+
+    // areas entirely outside the canvas are clipped to nothing
+    let mut canvas = packed::Canvas::new([0; 50], 10);
+    canvas.draw_packed(&packed::PackedImage::new(&[0x42, 0x30], 3), 20, 0);
+    canvas.copy_area(0, 1, 4, 1, 40, 0);
+    canvas.copy_area(40, 0, 4, 1, 0, 1);
+    assert_eq!(canvas.into_inner(), [0; 50]);
+
+    hal.done();
+}
+
+#[cfg(feature = "graphics")]
+#[test]
+fn test_canvas_draw_target() {
+    use embedded_graphics_core::{draw_target::DrawTarget, prelude::Point, Pixel};
+
+    let mut canvas = packed::Canvas::new([0x11; 2], 3);
+    canvas
+        .draw_iter([
+            Pixel(Point::new(1, 0), color::Color::RED),
+            Pixel(Point::new(-1, 0), color::Color::RED),
+            Pixel(Point::new(3, 0), color::Color::RED),
+        ])
+        .unwrap();
+    assert_eq!(canvas.into_inner(), [0x14, 0x11]);
+}
+
+#[cfg(feature = "graphics")]
+#[test]
+fn test_adjustments() {
+    use adjust::{Adjusted, Adjustments, Fixed};
+    use embedded_graphics_core::pixelcolor::Rgb888;
+    use quantize::Quantizer;
+
+    let identity = Adjustments::default().build();
+    for color in [
+        Rgb888::new(0, 0, 0),
+        Rgb888::new(12, 128, 255),
+        Rgb888::new(200, 100, 50),
+    ] {
+        assert_eq!(identity.apply(color), color);
+    }
+
+    let gamma = Adjustments {
+        gamma: Fixed::from_num(2),
+        ..Adjustments::default()
+    }
+    .build();
+    assert_eq!(
+        gamma.apply(Rgb888::new(64, 0, 255)),
+        Rgb888::new(128, 0, 255)
+    );
+
+    let contrast = Adjustments {
+        contrast: Fixed::from_num(2),
+        brightness: Fixed::from_num(-0.25),
+        ..Adjustments::default()
+    }
+    .build();
+    assert_eq!(
+        contrast.apply(Rgb888::new(128, 192, 64)),
+        Rgb888::new(1, 129, 0)
+    );
+
+    let white_point = Adjustments {
+        white_point: Rgb888::new(200, 255, 100),
+        ..Adjustments::default()
+    }
+    .build();
+    assert_eq!(
+        white_point.apply(Rgb888::new(200, 200, 150)),
+        Rgb888::new(255, 200, 255)
+    );
+
+    let curve = Adjustments {
+        tone_curve: &[(64, 0), (192, 255)],
+        ..Adjustments::default()
+    }
+    .build();
+    assert_eq!(
+        curve.apply(Rgb888::new(32, 128, 224)),
+        Rgb888::new(0, 127, 255)
+    );
+
+    let saturation = Adjustments {
+        saturation: Fixed::from_num(2),
+        ..Adjustments::default()
+    }
+    .build();
+    assert_eq!(
+        saturation.apply(Rgb888::new(100, 100, 100)),
+        Rgb888::new(100, 100, 100)
+    );
+    assert_eq!(
+        saturation.apply(Rgb888::new(150, 100, 100)),
+        Rgb888::new(185, 85, 85)
+    );
+
+    // extreme parameters saturate rather than overflow
+    let tiny_gamma = Adjustments {
+        gamma: Fixed::DELTA,
+        ..Adjustments::default()
+    }
+    .build();
+    assert_eq!(
+        tiny_gamma.apply(Rgb888::new(0, 254, 255)),
+        Rgb888::new(0, 0, 255)
+    );
+    let extreme = Adjustments {
+        brightness: Fixed::MAX,
+        contrast: Fixed::MIN,
+        saturation: Fixed::MAX,
+        ..Adjustments::default()
+    }
+    .build();
+    assert_eq!(
+        extreme.apply(Rgb888::new(0, 128, 255)),
+        Rgb888::new(0, 0, 0)
+    );
+    let extreme = Adjustments {
+        brightness: Fixed::MIN,
+        contrast: Fixed::MAX,
+        saturation: Fixed::MIN,
+        ..Adjustments::default()
+    }
+    .build();
+    assert_eq!(
+        extreme.apply(Rgb888::new(0, 128, 255)),
+        Rgb888::new(0, 0, 0)
+    );
+    let saturated = Adjustments {
+        saturation: Fixed::MAX,
+        ..Adjustments::default()
+    }
+    .build();
+    assert_eq!(
+        saturated.apply(Rgb888::new(255, 0, 0)),
+        Rgb888::new(255, 0, 0)
+    );
+
+    let mut quantizer = Adjusted::new(
+        &Adjustments {
+            saturation: Fixed::ZERO,
+            ..Adjustments::default()
+        },
+        quantize::Nearest,
+    );
+    assert_eq!(
+        quantizer.quantize(0, 0, Rgb888::new(0xff, 0xff, 0x50)),
+        color::Color::WHITE
+    );
+}
+
+#[cfg(feature = "graphics")]
+#[test]
+fn test_pattern_color() {
+    use embedded_graphics::{
+        prelude::*,
+        primitives::{Line, PrimitiveStyle, Rectangle},
+    };
+    use pattern::{PatternColor, Patterned};
+
+    assert_eq!(
+        PatternColor::PINK.resolve(Point::new(-1, 0)),
+        color::Color::WHITE
+    );
+    assert_eq!(
+        PatternColor::from(color::Color::GREEN).resolve(Point::new(3, 5)),
+        color::Color::GREEN
+    );
+
+    let mut canvas = packed::Canvas::new([0x11; 8], 4);
+    let mut target = Patterned::new(&mut canvas);
+    Rectangle::new(Point::new(-1, 0), Size::new(4, 2))
+        .into_styled(PrimitiveStyle::with_fill(PatternColor::BROWN))
+        .draw(&mut target)
+        .unwrap();
+    Line::new(Point::new(0, 3), Point::new(3, 3))
+        .into_styled(PrimitiveStyle::with_stroke(PatternColor::GRAY, 1))
+        .draw(&mut target)
+        .unwrap();
+    assert_eq!(
+        canvas.into_inner(),
+        [0x40, 0x41, 0x04, 0x01, 0x11, 0x11, 0x10, 0x10]
+    );
+}
+
+/// Records I2C writes and answers reads from a queue.
+#[cfg(feature = "inkplate6color")]
+#[derive(Default)]
+struct FakeI2c {
+    writes: std::vec::Vec<(u8, std::vec::Vec<u8>)>,
+    reads: std::collections::VecDeque<std::vec::Vec<u8>>,
+}
+
+#[cfg(feature = "inkplate6color")]
+impl embedded_hal::i2c::ErrorType for FakeI2c {
+    type Error = core::convert::Infallible;
+}
+
+#[cfg(feature = "inkplate6color")]
+impl embedded_hal::i2c::I2c for FakeI2c {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [embedded_hal::i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        for operation in operations {
+            match operation {
+                embedded_hal::i2c::Operation::Write(bytes) => {
+                    self.writes.push((address, bytes.to_vec()));
+                }
+                embedded_hal::i2c::Operation::Read(buffer) => {
+                    buffer.copy_from_slice(&self.reads.pop_front().unwrap());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "inkplate6color")]
+#[test]
+fn test_io_expander() {
+    use inkplate6color::{ExpanderPins, IoExpander};
+
+    let mut hal = Hal::new(&[]);
+    let mut delay = hal.clone().delay();
+    hal.update_expectations(&[delay.expect_delay_ns(1_000_000)]);
+
+    let mut expander = IoExpander::new(FakeI2c::default(), ExpanderPins::default());
+    expander.set_panel_power(true).unwrap();
+    assert_eq!(expander.battery_mv(&mut delay, || 1_850).unwrap(), 3_700);
+    expander.set_panel_power(false).unwrap();
+
+    assert_eq!(
+        expander.release().writes,
+        [
+            (0x20, std::vec![0x14, 0x00, 0b0000_0100]),
+            (0x20, std::vec![0x00, 0xff, 0b1111_1011]),
+            (0x20, std::vec![0x14, 0x00, 0b0000_0110]),
+            (0x20, std::vec![0x00, 0xff, 0b1111_1001]),
+            (0x20, std::vec![0x14, 0x00, 0b0000_0100]),
+            (0x20, std::vec![0x14, 0x00, 0b0000_0000]),
+        ]
+    );
+
+    hal.done();
+}
+
+#[cfg(feature = "inkplate6color")]
+#[test]
+fn test_rtc() {
+    use inkplate6color::{Alarm, DateTime, Rtc};
+
+    let mut i2c = FakeI2c::default();
+    i2c.reads
+        .push_back(std::vec![0x80 | 0x59, 0x30, 0x23, 0x31, 0x06, 0x12, 0x24]);
+    i2c.reads.push_back(std::vec![0b0100_0000]);
+    i2c.reads.push_back(std::vec![0b0100_0000]);
+    i2c.reads.push_back(std::vec![0b1000_0000]);
+
+    let mut rtc = Rtc::new(i2c);
+    assert_eq!(
+        rtc.time().unwrap(),
+        DateTime {
+            year: 24,
+            month: 12,
+            day: 31,
+            weekday: 6,
+            hour: 23,
+            minute: 30,
+            second: 59,
+        }
+    );
+    assert!(rtc.alarm_fired().unwrap());
+    rtc.set_alarm(&Alarm {
+        minute: Some(15),
+        hour: Some(7),
+        ..Alarm::default()
+    })
+    .unwrap();
+    rtc.clear_alarm().unwrap();
+
+    assert_eq!(
+        rtc.release().writes,
+        [
+            (0x51, std::vec![0x04]),
+            (0x51, std::vec![0x01]),
+            (0x51, std::vec![0x0b, 0x80, 0x15, 0x07, 0x80, 0x80]),
+            (0x51, std::vec![0x01]),
+            (0x51, std::vec![0x01, 0b1000_0000]),
+            (0x51, std::vec![0x01]),
+            (0x51, std::vec![0x01, 0b0000_0000]),
+            (0x51, std::vec![0x0b, 0x80, 0x80, 0x80, 0x80, 0x80]),
+        ]
+    );
+}
+
+#[test]
+fn test_display_observed() {
+    use refresh::{Phase, RefreshObserver};
+
+    #[derive(Default)]
+    struct Recorder(std::vec::Vec<(Phase, Option<u32>)>);
+
+    impl RefreshObserver for Recorder {
+        fn started(&mut self, phase: Phase) {
+            self.0.push((phase, None));
+        }
+
+        fn finished(&mut self, phase: Phase, busy_us: u32) {
+            self.0.push((phase, Some(busy_us)));
+        }
+    }
+
+    let mut hal = Hal::new(&[]);
+
+    let spi = hal.clone().spi();
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    hal.update_expectations(&[
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x61),
+        spi.expect_transaction_end(),
+        dc.expect_set(PinState::High),
+        spi.expect_transaction_start(),
+        spi.expect_write_vec([0x02, 0x58, 0x01, 0xc0].to_vec()),
+        spi.expect_transaction_end(),
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x10),
+        spi.expect_transaction_end(),
+        dc.expect_set(PinState::High),
+        spi.expect_transaction_start(),
+        spi.expect_write_vec([0b00010001; super::WIDTH * super::HEIGHT / 2].to_vec()),
+        spi.expect_transaction_end(),
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x04),
+        spi.expect_transaction_end(),
+        busy.expect_get(PinState::High),
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x12),
+        spi.expect_transaction_end(),
+        busy.expect_get(PinState::Low),
+        delay.expect_delay_ns(1_000_000),
+        busy.expect_get(PinState::Low),
+        delay.expect_delay_ns(1_000_000),
+        busy.expect_get(PinState::High),
+        dc.expect_set(PinState::Low),
+        spi.expect_transaction_start(),
+        spi.expect_write(0x04),
+        spi.expect_transaction_end(),
+        busy.expect_get(PinState::High),
+        delay.expect_delay_ns(1_000_000),
+        busy.expect_get(PinState::Low),
+        delay.expect_delay_ns(200_000_000),
+    ]);
+
+    let mut recorder = Recorder::default();
+    let stats = Display {
+        interface: FourWire { spi, dc },
+        rst,
+        busy,
+        delay,
+        buffer: [0b0001_0001; (WIDTH * HEIGHT) / 2],
+        chunk_size: FRAME_CHUNK,
+        timings: Timings::default(),
+        config: PanelConfig::default(),
+        state: PhantomData::<state::Awake>,
+    }
+    .display_observed(&mut recorder)
+    .unwrap();
+
+    assert_eq!(
+        stats,
+        RefreshStats {
+            reset_busy_us: 0,
+            power_on_busy_us: 0,
+            refresh_busy_us: 2_000,
+            power_off_busy_us: 1_000,
+        }
+    );
+    assert_eq!(stats.total_busy_us(), 3_000);
+    assert_eq!(
+        recorder.0,
+        [
+            (Phase::Transfer, None),
+            (Phase::Transfer, Some(0)),
+            (Phase::PowerOn, None),
+            (Phase::PowerOn, Some(0)),
+            (Phase::Refresh, None),
+            (Phase::Refresh, Some(2_000)),
+            (Phase::PowerOff, None),
+            (Phase::PowerOff, Some(1_000)),
+        ]
+    );
+
+    hal.done();
+}
+
+#[test]
+fn test_read_unsupported() {
+    use embedded_hal::digital;
+
+    /// An interface that can only write.
+    struct WriteOnly;
+
+    impl Interface for WriteOnly {
+        type DataCommandError = digital::ErrorKind;
+        type SpiError = spi::ErrorKind;
+
+        fn send_command<BUSY, RST>(
+            &mut self,
+            _command: u8,
+        ) -> Result<(), error::Error<BUSY, RST, digital::ErrorKind, spi::ErrorKind>>
+        where
+            BUSY: digital::Error,
+            RST: digital::Error,
+        {
+            Ok(())
+        }
+
+        fn send_data<BUSY, RST>(
+            &mut self,
+            _data: &[u8],
+        ) -> Result<(), error::Error<BUSY, RST, digital::ErrorKind, spi::ErrorKind>>
+        where
+            BUSY: digital::Error,
+            RST: digital::Error,
+        {
+            Ok(())
+        }
+    }
+
+    let error = WriteOnly
+        .read_data::<digital::ErrorKind, digital::ErrorKind>(&mut [0])
+        .unwrap_err();
+    assert_eq!(error, error::Error::ReadUnsupported);
+    assert_eq!(error.kind(), error::ErrorKind::InvalidState);
+}
+
+/// Records SPI writes and answers reads from a queue.  Writes fail once `write_limit` have been
+/// recorded.
+#[derive(Default)]
+struct FakeSpi {
+    writes: std::vec::Vec<std::vec::Vec<u8>>,
+    reads: std::collections::VecDeque<std::vec::Vec<u8>>,
+    write_limit: Option<usize>,
+}
+
+impl spi::ErrorType for FakeSpi {
+    type Error = spi::ErrorKind;
+}
+
+impl spi::SpiDevice for FakeSpi {
+    fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        for operation in operations {
+            match operation {
+                spi::Operation::Write(_)
+                    if self
+                        .write_limit
+                        .is_some_and(|limit| self.writes.len() >= limit) =>
+                {
+                    return Err(spi::ErrorKind::Other)
+                }
+                spi::Operation::Write(bytes) => self.writes.push(bytes.to_vec()),
+                spi::Operation::Read(buffer) => {
+                    buffer.copy_from_slice(&self.reads.pop_front().unwrap());
+                }
+                _ => return Err(spi::ErrorKind::Other),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_low_power() {
+    use config::{LowPowerThreshold, PanelConfig};
+
+    let mut hal = Hal::new(&[]);
+
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    hal.update_expectations(&[
+        dc.expect_set(PinState::Low),
+        dc.expect_set(PinState::High),
+        dc.expect_set(PinState::Low),
+        dc.expect_set(PinState::High),
+    ]);
+
+    let mut spi = FakeSpi::default();
+    spi.reads.push_back(std::vec![0x01]);
+    spi.reads.push_back(std::vec![0x00]);
+
+    let mut epd = Display {
+        interface: FourWire { spi, dc },
+        rst,
+        busy,
+        delay,
+        buffer: [0b0001_0001; (WIDTH * HEIGHT) / 2],
+        chunk_size: FRAME_CHUNK,
+        timings: Timings::default(),
+        config: PanelConfig::default(),
+        state: PhantomData::<state::Awake>,
+    };
+    assert!(!epd.low_power_detected().unwrap());
+
+    epd.set_config(PanelConfig {
+        low_power_threshold: Some(LowPowerThreshold::V2_3),
+        refuse_low_power: true,
+        ..PanelConfig::default()
+    });
+    let error = epd.display().unwrap_err();
+    assert_eq!(error, error::Error::LowPower);
+    assert_eq!(error.kind(), error::ErrorKind::LowPower);
+    // nothing was sent after the check
+    assert_eq!(epd.interface.spi.writes, [[0x51], [0x51]]);
+
+    hal.done();
+}
+
+#[test]
+fn test_vcom() {
+    use config::Vcom;
+
+    assert_eq!(Vcom::from_millivolts(-100).unwrap().bits(), 0);
+    assert_eq!(Vcom::from_millivolts(-4_000).unwrap().bits(), 78);
+    assert_eq!(Vcom::from_millivolts(-1_520).unwrap().millivolts(), -1_500);
+    assert_eq!(Vcom::from_millivolts(-1_530).unwrap().millivolts(), -1_550);
+    assert_eq!(Vcom::from_millivolts(-50), None);
+    assert_eq!(Vcom::from_millivolts(-4_050), None);
+
+    let mut hal = Hal::new(&[]);
+
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    hal.update_expectations(&[
+        dc.expect_set(PinState::Low),
+        busy.expect_get(PinState::High),
+        dc.expect_set(PinState::Low),
+        dc.expect_set(PinState::High),
+        busy.expect_get(PinState::High),
+        dc.expect_set(PinState::Low),
+        dc.expect_set(PinState::High),
+        dc.expect_set(PinState::Low),
+        busy.expect_get(PinState::Low),
+        delay.expect_delay_ns(200_000_000),
+    ]);
+
+    let mut spi = FakeSpi::default();
+    spi.reads.push_back(std::vec![0x1c]);
+
+    let mut epd = Display {
+        interface: FourWire { spi, dc },
+        rst,
+        busy,
+        delay,
+        buffer: [0b0001_0001; (WIDTH * HEIGHT) / 2],
+        chunk_size: FRAME_CHUNK,
+        timings: Timings::default(),
+        config: PanelConfig::default(),
+        state: PhantomData::<state::Awake>,
+    };
+    assert_eq!(epd.measure_vcom().unwrap().millivolts(), -1_500);
+    assert_eq!(
+        epd.interface.spi.writes,
+        [[0x04], [0x80], [0x11], [0x81], [0x02]]
+    );
+
+    hal.done();
+}
+
+#[test]
+fn test_verify_transfer() {
+    let mut hal = Hal::new(&[]);
+
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    let transfer = [
+        dc.expect_set(PinState::Low),
+        dc.expect_set(PinState::High),
+        dc.expect_set(PinState::Low),
+        dc.expect_set(PinState::High),
+        dc.expect_set(PinState::Low),
+        dc.expect_set(PinState::High),
+    ];
+    let mut expectations = std::vec::Vec::new();
+    expectations.extend_from_slice(&transfer);
+    expectations.extend_from_slice(&transfer);
+    expectations.extend_from_slice(&[
+        dc.expect_set(PinState::Low),
+        busy.expect_get(PinState::High),
+        dc.expect_set(PinState::Low),
+        busy.expect_get(PinState::High),
+        dc.expect_set(PinState::Low),
+        busy.expect_get(PinState::Low),
+        delay.expect_delay_ns(200_000_000),
+    ]);
+    expectations.extend_from_slice(&transfer);
+    hal.update_expectations(&expectations);
+
+    let mut spi = FakeSpi::default();
+    spi.reads.push_back(std::vec![0x00]);
+    spi.reads.push_back(std::vec![0x80]);
+    spi.reads.push_back(std::vec![0x00]);
+
+    let mut epd = Display {
+        interface: FourWire { spi, dc },
+        rst,
+        busy,
+        delay,
+        buffer: [0b0001_0001; (WIDTH * HEIGHT) / 2],
+        chunk_size: FRAME_CHUNK,
+        timings: Timings::default(),
+        config: PanelConfig {
+            verify_transfer: true,
+            transfer_retries: 1,
+            ..PanelConfig::default()
+        },
+        state: PhantomData::<state::Awake>,
+    };
+    epd.display().unwrap();
+    assert_eq!(epd.interface.spi.writes.len(), 2 * 5 + 3);
+
+    epd.config.transfer_retries = 0;
+    let error = epd.display().unwrap_err();
+    assert_eq!(error, error::Error::TransferIncomplete);
+    assert_eq!(error.kind(), error::ErrorKind::InvalidState);
+
+    hal.done();
+}
+
+#[test]
+fn test_sleep_after_failed_refresh() {
+    let mut hal = Hal::new(&[]);
+
+    let rst = hal.clone().pin();
+    let dc = hal.clone().pin();
+    let busy = hal.clone().pin();
+    let delay = hal.clone().delay();
+
+    let refresh = || {
+        let mut refresh = std::vec![
+            rst.expect_set(PinState::Low),
+            delay.expect_delay_ns(1_000_000),
+            rst.expect_set(PinState::High),
+            delay.expect_delay_ns(200_000_000),
+            busy.expect_get(PinState::High),
+        ];
+        // init sequence
+        for _ in 0..9 {
+            refresh.extend([dc.expect_set(PinState::Low), dc.expect_set(PinState::High)]);
+        }
+        refresh.extend([
+            delay.expect_delay_ns(100_000_000),
+            dc.expect_set(PinState::Low),
+            dc.expect_set(PinState::High),
+        ]);
+        // transfer and DATA_STOP
+        for _ in 0..3 {
+            refresh.extend([dc.expect_set(PinState::Low), dc.expect_set(PinState::High)]);
+        }
+        refresh
+    };
+    let mut expectations = refresh();
+    // deep sleep
+    expectations.extend([
+        delay.expect_delay_ns(10_000_000),
+        dc.expect_set(PinState::Low),
+        dc.expect_set(PinState::High),
+        delay.expect_delay_ns(100_000_000),
+        rst.expect_set(PinState::Low),
+        dc.expect_set(PinState::Low),
+    ]);
+    // again, with deep sleep failing at its first command
+    expectations.extend(refresh());
+    expectations.extend([
+        delay.expect_delay_ns(10_000_000),
+        dc.expect_set(PinState::Low),
+    ]);
+    hal.update_expectations(&expectations);
+
+    let mut spi = FakeSpi::default();
+    spi.reads.push_back(std::vec![0x00]);
+    spi.reads.push_back(std::vec![0x00]);
+
+    let mut epd = Display {
+        interface: FourWire { spi, dc },
+        rst,
+        busy,
+        delay,
+        buffer: [0b0001_0001; (WIDTH * HEIGHT) / 2],
+        chunk_size: FRAME_CHUNK,
+        timings: Timings::default(),
+        config: PanelConfig {
+            verify_transfer: true,
+            ..PanelConfig::default()
+        },
+        state: PhantomData::<state::Asleep>,
+    };
+    assert_eq!(epd.display(), Err(error::Error::TransferIncomplete));
+    let writes = &epd.interface.spi.writes;
+    assert_eq!(writes[writes.len() - 2..], [[0x07], [0xA5]]);
+
+    // a failure to sleep does not hide why the refresh failed
+    epd.interface.spi.write_limit = Some(2 * writes.len() - 2);
+    assert_eq!(epd.display(), Err(error::Error::TransferIncomplete));
+
+    hal.done();
+}
+
+#[test]
+fn test_compress() {
+    use compress::{compress, max_compressed_len, Decompressor};
+
+    let mut frame = std::vec![0x11; (WIDTH * HEIGHT) / 2];
+    frame[10..300].fill(0x23);
+    for (i, byte) in frame[1_000..1_500].iter_mut().enumerate() {
+        *byte = i.to_le_bytes()[0];
+    }
+    frame[2_000..2_002].fill(0x45);
+
+    let mut compressed = std::vec![0; max_compressed_len(frame.len())];
+    let len = compress(&frame, &mut compressed).unwrap();
+    assert!(len < 3_000);
+    assert_eq!(
+        compress(&frame, &mut compressed[..len - 1]),
+        Err(compress::Error::OutputFull)
+    );
+
+    // odd chunk sizes split tokens
+    let mut decompressor = Decompressor::new(&compressed[..len]);
+    let mut decompressed = std::vec::Vec::new();
+    let mut chunk = [0; 37];
+    loop {
+        let n = decompressor.fill(&mut chunk).unwrap();
+        decompressed.extend_from_slice(&chunk[..n]);
+        if n < chunk.len() {
+            break;
+        }
+    }
+    assert!(decompressor.is_finished());
+    assert_eq!(decompressed, frame);
+
+    // literal bytes still owed
+    let mut decompressor = Decompressor::new(&[0x02]);
+    assert_eq!(
+        decompressor.fill(&mut chunk),
+        Err(compress::Error::Truncated)
+    );
+    assert!(!decompressor.is_finished());
+
+    let mut hal = Hal::new(&[]);
+    let mut epd = Display::new(
+        hal.clone().spi(),
+        hal.clone().pin(),
+        hal.clone().pin(),
+        hal.clone().pin(),
+        hal.clone().delay(),
+    );
+    epd.draw_compressed(&mut Decompressor::new(&compressed[..len]))
+        .unwrap();
+    assert_eq!(epd.buffer[..], frame[..]);
+    assert_eq!(
+        epd.draw_compressed(&mut Decompressor::new(&compressed[..len - 1])),
+        Err(compress::Error::Truncated)
+    );
+    assert_eq!(
+        epd.draw_compressed(&mut Decompressor::new(&[0x80, 0x11])),
+        Err(compress::Error::TooShort)
+    );
+    let mut trailing = compressed[..len].to_vec();
+    trailing.extend_from_slice(&[0x00, 0x11]);
+    assert_eq!(
+        epd.draw_compressed(&mut Decompressor::new(&trailing)),
+        Err(compress::Error::TooLong)
+    );
+    hal.done();
+}
+
+#[cfg(feature = "ratatui")]
+#[test]
+fn test_ratatui_backend() {
+    use color::Color;
+    use embedded_graphics::mono_font::ascii::FONT_6X10;
+    use packed::Canvas;
+    use ratatui::DisplayBackend;
+    use ratatui_core::{
+        backend::{Backend, ClearType},
+        buffer::Cell,
+        layout::Size,
+        style::{Color as TermColor, Modifier},
+    };
+
+    let mut canvas = Canvas::new(std::vec![0x11; 30 * 20 / 2], 30);
+    let mut backend = DisplayBackend::new(&mut canvas, &FONT_6X10);
+    assert_eq!(backend.size().unwrap(), Size::new(5, 2));
+
+    let mut red_on_blue = Cell::new("A");
+    red_on_blue.set_fg(TermColor::Red).set_bg(TermColor::Blue);
+    let mut reversed = Cell::new(" ");
+    reversed
+        .set_fg(TermColor::Indexed(226))
+        .set_bg(TermColor::Reset)
+        .modifier = Modifier::REVERSED;
+    backend
+        .draw([(1, 0, &red_on_blue), (0, 1, &reversed)].into_iter())
+        .unwrap();
+    backend.flush().unwrap();
+
+    // background, then a pixel of the glyph
+    assert_eq!(canvas.pixel(6, 0), Some(Color::BLUE));
+    assert!((6..12)
+        .flat_map(|x| (0..10).map(move |y| (x, y)))
+        .any(|(x, y)| canvas.pixel(x, y) == Some(Color::RED)));
+    assert_eq!(canvas.pixel(0, 10), Some(Color::YELLOW));
+    assert_eq!(canvas.pixel(5, 19), Some(Color::YELLOW));
+
+    let mut backend = DisplayBackend::new(&mut canvas, &FONT_6X10);
+    backend.set_cursor_position((1, 0)).unwrap();
+    backend.clear_region(ClearType::AfterCursor).unwrap();
+    // muted text stays visible on the default background
+    let mut gray = Cell::new(" ");
+    gray.set_bg(TermColor::Gray);
+    backend.draw([(4, 1, &gray)].into_iter()).unwrap();
+    assert_eq!(backend.target_mut().pixel(24, 10), Some(Color::BLACK));
+    assert_eq!(canvas.pixel(6, 0), Some(Color::WHITE));
+    assert_eq!(canvas.pixel(0, 10), Some(Color::WHITE));
+}
+
+#[cfg(feature = "graphics")]
+#[test]
+fn test_converted() {
+    use color::Color;
+    use convert::Converted;
+    use embedded_graphics::{
+        pixelcolor::{BinaryColor, Gray4, Gray8, Rgb555, Rgb565},
+        prelude::*,
+        primitives::{PrimitiveStyle, Rectangle},
+    };
+    use packed::Canvas;
+
+    assert_eq!(Color::from(Rgb565::RED), Color::RED);
+    assert_eq!(Color::from(Rgb555::BLUE), Color::BLUE);
+    assert_eq!(Color::from(Gray8::new(0x7f)), Color::BLACK);
+    assert_eq!(Color::from(Gray4::new(0x8)), Color::WHITE);
+    assert_eq!(Color::from(BinaryColor::On), Color::BLACK);
+
+    let mut canvas = Canvas::new(std::vec![0x11; 4 * 2 / 2], 4);
+    Rectangle::new(Point::zero(), Size::new(2, 1))
+        .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+        .draw(&mut Converted::new(&mut canvas))
+        .unwrap();
+    Pixel(Point::new(3, 1), BinaryColor::On)
+        .draw(&mut Converted::with_map(&mut canvas, |color| match color {
+            BinaryColor::On => Color::RED,
+            BinaryColor::Off => Color::WHITE,
+        }))
+        .unwrap();
+    Pixel(Point::new(0, 1), Rgb565::GREEN)
+        .draw(&mut Converted::new(&mut canvas))
+        .unwrap();
+
+    assert_eq!(canvas.into_inner(), [0x00, 0x11, 0x21, 0x14]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    use color::Color;
+    use config::{FrameRate, PanelConfig, Vcom};
+    use packed::{Canvas, PackedImage};
+    use serde_test::{
+        assert_de_tokens, assert_de_tokens_error, assert_ser_tokens, assert_tokens, Configure,
+        Token,
+    };
+
+    assert_tokens(&Color::RED.readable(), &[Token::Str("red")]);
+    assert_tokens(&Color::RED.compact(), &[Token::U8(4)]);
+    assert_de_tokens(&Color::ORANGE.readable(), &[Token::Str("Orange")]);
+    assert_de_tokens(&Color::ORANGE.readable(), &[Token::U8(6)]);
+    assert_de_tokens_error::<serde_test::Readable<Color>>(
+        &[Token::U8(7)],
+        "invalid value: integer `7`, expected a color name or value",
+    );
+
+    assert_tokens(
+        &Vcom::from_millivolts(-1_500).unwrap(),
+        &[Token::I16(-1_500)],
+    );
+    assert_de_tokens_error::<Vcom>(
+        &[Token::I16(-50)],
+        "invalid value: integer `-50`, expected a VCOM from -4000 to -100 mV",
+    );
+
+    // missing fields take their defaults
+    assert_de_tokens(
+        &PanelConfig {
+            frame_rate: Some(FrameRate::Hz100),
+            ..PanelConfig::default()
+        },
+        &[
+            Token::Struct {
+                name: "PanelConfig",
+                len: 1,
+            },
+            Token::Str("frame_rate"),
+            Token::Some,
+            Token::UnitVariant {
+                name: "FrameRate",
+                variant: "Hz100",
+            },
+            Token::StructEnd,
+        ],
+    );
+
+    const DATA: &[u8] = &[0x01, 0x23, 0x45, 0x60];
+    let image = PackedImage::new(DATA, 3);
+    let tokens = [
+        Token::Struct {
+            name: "PackedImage",
+            len: 2,
+        },
+        Token::Str("width"),
+        Token::U64(3),
+        Token::Str("data"),
+        Token::BorrowedBytes(DATA),
+        Token::StructEnd,
+    ];
+    assert_tokens(&image, &tokens);
+    assert_ser_tokens(&Canvas::new(DATA, 3), &tokens);
+    assert_de_tokens_error::<PackedImage<'_>>(
+        &[
+            Token::Seq { len: Some(2) },
+            Token::U64(3),
+            Token::BorrowedBytes(&DATA[..3]),
+            Token::SeqEnd,
+        ],
+        "invalid length 3, expected whole rows of packed pixels",
+    );
+    assert_de_tokens_error::<PackedImage<'_>>(
+        &[
+            Token::Seq { len: Some(2) },
+            Token::U64(0),
+            Token::BorrowedBytes(DATA),
+            Token::SeqEnd,
+        ],
+        "invalid length 4, expected whole rows of packed pixels",
+    );
+
+    // round trips through real formats
+    let config = PanelConfig {
+        vcom: Vcom::from_millivolts(-1_500),
+        verify_transfer: true,
+        ..PanelConfig::default()
+    };
+    let json = serde_json::to_string(&(Color::BLUE, config)).unwrap();
+    assert_eq!(
+        serde_json::from_str::<(Color, PanelConfig)>(&json).unwrap(),
+        (Color::BLUE, config)
+    );
+    let bytes = postcard::to_allocvec(&image).unwrap();
+    assert_eq!(bytes.len(), 2 + DATA.len());
+    assert_eq!(
+        postcard::from_bytes::<PackedImage<'_>>(&bytes).unwrap(),
+        image
+    );
+    let json = serde_json::to_string(&image).unwrap();
+    assert!(serde_json::from_str::<PackedImage<'_>>(&json).is_err());
+}
+
+#[test]
+fn test_glyph_atlas() {
+    use atlas::{GlyphAtlas, MAGIC};
+    use color::Color;
+    use packed::Canvas;
+
+    // two 3x2 glyphs: a red heart with transparent corners and a solid yellow sun
+    let mut data = MAGIC.to_vec();
+    data.extend_from_slice(&[3, 2, 2, 0]);
+    data.extend_from_slice(&u32::from('❤').to_le_bytes());
+    data.extend_from_slice(&u32::from('☀').to_le_bytes());
+    data.extend_from_slice(&[0x74, 0x75, 0x55, 0x44, 0x45, 0x55]);
+
+    let atlas = GlyphAtlas::new(&data).unwrap();
+    assert_eq!(
+        GlyphAtlas::new(&data[..data.len() - 1]),
+        Err(atlas::Error::Truncated)
+    );
+    assert_eq!(GlyphAtlas::new(b"GLA3"), Err(atlas::Error::InvalidHeader));
+    assert_eq!(
+        std::format!("{}", atlas::Error::Truncated),
+        "glyph atlas truncated"
+    );
+    assert!(atlas.glyph('x').is_none());
+
+    let heart = atlas.glyph('❤').unwrap();
+    assert_eq!((heart.width(), heart.height()), (3, 2));
+    assert_eq!(heart.pixel(0, 0), None);
+    assert_eq!(heart.pixel(1, 0), Some(Color::RED));
+    let sun = atlas.glyph('☀').unwrap();
+    assert_eq!(sun.pixel(0, 0), Some(Color::YELLOW));
+
+    let mut canvas = Canvas::new(std::vec![0x11; 4], 4);
+    canvas.draw_glyph(&heart, 1, 0);
+    assert_eq!(canvas.into_inner(), [0x11, 0x41, 0x14, 0x44]);
+
+    #[cfg(feature = "text")]
+    {
+        use embedded_graphics::{
+            mono_font::{ascii::FONT_6X10, MonoTextStyle},
+            prelude::Point,
+        };
+
+        let mut canvas = Canvas::new(std::vec![0x11; 20 * 10 / 2], 20);
+        let style = MonoTextStyle::new(&FONT_6X10, Color::BLACK);
+        let next = atlas.draw_text(&mut canvas, "☀I", Point::zero(), style);
+        assert_eq!(next, Point::new(9, 0));
+        assert_eq!(canvas.pixel(0, 0), Some(Color::YELLOW));
+        // the I follows the glyph
+        assert!((3..9)
+            .flat_map(|x| (0..10).map(move |y| (x, y)))
+            .any(|(x, y)| canvas.pixel(x, y) == Some(Color::BLACK)));
+        assert!((0..3).all(|x| canvas.pixel(x, 5) == Some(Color::WHITE)));
+    }
+}
@@ -0,0 +1,40 @@
+/// Delays used when driving the panel, in microseconds.  Defaults match the Soldered Inkplate
+/// library.
+#[derive(Debug, Eq, Hash, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Timings {
+    /// Duration RST is held low when resetting the panel.
+    pub reset_pulse_us: u32,
+    /// Wait after releasing RST before the controller is configured.
+    pub reset_wait_us: u32,
+    /// Wait before sending the deep sleep command.
+    pub sleep_setup_us: u32,
+    /// Wait after the deep sleep command before RST is pulled low.
+    pub sleep_hold_us: u32,
+    /// Wait before the final VCOM and data interval write of the init sequence.
+    pub vcom_setup_us: u32,
+    /// Wait after the panel has powered off following a refresh.
+    pub power_off_us: u32,
+    /// Interval between reads of BUSY while waiting on the controller.
+    pub busy_poll_us: u32,
+    /// Maximum time to wait on BUSY before giving up with
+    /// [`crate::error::Error::BusyTimeout`].  `None` waits forever.
+    pub busy_timeout_us: Option<u32>,
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Self {
+            reset_pulse_us: 1_000,
+            reset_wait_us: 200_000,
+            sleep_setup_us: 10_000,
+            sleep_hold_us: 100_000,
+            vcom_setup_us: 100_000,
+            power_off_us: 200_000,
+            busy_poll_us: 1_000,
+            busy_timeout_us: None,
+        }
+    }
+}